@@ -11,3 +11,23 @@ pub fn require_admin(env: &Env, caller: &Address) {
         panic_with_error!(env, CreditLineError::NotAdmin);
     }
 }
+
+/// Require that the caller may underwrite a pending loan: the admin, a risk
+/// operator, or the loan's own merchant confirming the sale.
+pub fn require_underwriter(env: &Env, caller: &Address, merchant: &Address) {
+    let admin = storage::get_admin(env);
+
+    if caller != &admin && caller != merchant && !storage::is_risk_operator(env, caller) {
+        panic_with_error!(env, CreditLineError::UnauthorizedApprover);
+    }
+}
+
+/// Require that the caller may restructure an active loan: the admin or a
+/// risk operator.
+pub fn require_restructurer(env: &Env, caller: &Address) {
+    let admin = storage::get_admin(env);
+
+    if caller != &admin && !storage::is_risk_operator(env, caller) {
+        panic_with_error!(env, CreditLineError::UnauthorizedRestructurer);
+    }
+}
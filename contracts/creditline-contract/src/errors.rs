@@ -1,6 +1,7 @@
 use soroban_sdk::contracterror;
 
-// Error types for the creditline contract
+// Error types for the creditline contract. Soroban allows at most 50 cases
+// in a contract error enum, so reuse an existing code where one fits.
 #[contracterror]
 #[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
 #[repr(u32)]
@@ -27,4 +28,32 @@ pub enum CreditLineError {
     ParametersUnavailable = 20,
     ReentrancyDetected = 21,
     LoanInGracePeriod = 22,
+    LoanNotPending = 23,
+    UnauthorizedApprover = 24,
+    LoanRequestExpired = 25,
+    EmptyRepaymentSchedule = 26,
+    InvalidInstallmentAmount = 27,
+    InstallmentDueDateInPast = 28,
+    UnsortedRepaymentSchedule = 29,
+    TooManyInstallments = 30,
+    TenorTooLong = 31,
+    ScheduleAmountMismatch = 32,
+    InvalidRepaymentTerms = 33,
+    UnauthorizedRestructurer = 34,
+    RestructureNotProposed = 35,
+    InvalidQuoteTimestamp = 36,
+    LoanNotDefaulted = 37,
+    UnauthorizedMerchant = 38,
+    RefundExceedsPurchasePrice = 39,
+    RevolvingLineExists = 40,
+    RevolvingLineNotFound = 41,
+    RevolvingLineClosed = 42,
+    RevolvingLineNotSettled = 43,
+    StatementsPending = 44,
+    StatementNotFound = 45,
+    AssetNotSupported = 46,
+    BatchTooLarge = 47,
+    InvalidBeneficiary = 48,
+    InvalidAssetPool = 49,
+    RevolvingLineDelinquent = 50,
 }
@@ -1,6 +1,6 @@
 use soroban_sdk::{symbol_short, Address, Env, Symbol, Vec};
 
-use crate::types::RepaymentInstallment;
+use crate::types::{RepaymentInstallment, RevolvingStatement};
 
 // Event topics
 const LOAN_CREATED: Symbol = symbol_short!("LOANCRTD");
@@ -10,6 +10,29 @@ const LOAN_REPAID: Symbol = symbol_short!("LOANRPD");
 const LOAN_CANCELLED: Symbol = symbol_short!("LOANCNCL");
 const LOAN_LATE_FEE: Symbol = symbol_short!("LOANLTFE");
 const LOAN_GRACE_PERIOD: Symbol = symbol_short!("LOANGRC");
+const LOAN_FUNDED: Symbol = symbol_short!("LOANFUND");
+const LOAN_REJECTED: Symbol = symbol_short!("LOANRJCT");
+const INSTALLMENT_PAID: Symbol = symbol_short!("LOANINST");
+const RISK_OPERATOR_CHANGED: Symbol = symbol_short!("RISKOPCHG");
+const ASSET_CONFIGURED: Symbol = symbol_short!("ASSETCFG");
+const RESTRUCTURE_PROPOSED: Symbol = symbol_short!("LOANRSPR");
+const LOAN_RESTRUCTURED: Symbol = symbol_short!("LOANRSTR");
+const EARLY_PAYOFF_REBATE: Symbol = symbol_short!("LOANRBTE");
+const REPAYMENT_SPONSORED: Symbol = symbol_short!("LOANSPRP");
+const KEEPER_BOUNTY: Symbol = symbol_short!("KEEPBNTY");
+const LOAN_RECOVERY: Symbol = symbol_short!("LOANRCVR");
+const MERCHANT_REFUND: Symbol = symbol_short!("LOANRFND");
+const RECEIVABLE_TRANSFERRED: Symbol = symbol_short!("RCVXFER");
+const LINE_OPENED: Symbol = symbol_short!("LINEOPEN");
+const LINE_DRAWN: Symbol = symbol_short!("LINEDRAW");
+const LINE_REPAID: Symbol = symbol_short!("LINERPAY");
+const LINE_STATEMENT: Symbol = symbol_short!("LINESTMT");
+const LINE_CLOSED: Symbol = symbol_short!("LINECLSD");
+const LINE_DEFAULTED: Symbol = symbol_short!("LINEDFLT");
+// Cancellation reasons carried in the `LOANCNCL` payload
+pub const CANCEL_REASON_MANUAL: Symbol = symbol_short!("manual");
+pub const CANCEL_REASON_EXPIRED: Symbol = symbol_short!("expired");
+
 /// SC-11: emitted after a successful reputation score change triggered by the CreditLine
 const REPUTATION_UPDATED: Symbol = symbol_short!("REPUPD");
 
@@ -54,6 +77,54 @@ pub fn emit_loan_requested(
     );
 }
 
+/// Emitted when a pending loan request is approved and funded by the pool.
+pub fn emit_loan_funded(
+    env: &Env,
+    borrower: &Address,
+    loan_id: u64,
+    approver: &Address,
+    merchant: &Address,
+    pool_contribution: i128,
+) {
+    env.events().publish(
+        (LOAN_FUNDED, borrower, loan_id),
+        (
+            approver.clone(),
+            merchant.clone(),
+            pool_contribution,
+            env.ledger().timestamp(),
+        ),
+    );
+}
+
+/// Emitted when a pending loan request is rejected and the guarantee refunded.
+pub fn emit_loan_rejected(
+    env: &Env,
+    borrower: &Address,
+    loan_id: u64,
+    rejected_by: &Address,
+    refunded_guarantee: i128,
+) {
+    env.events().publish(
+        (LOAN_REJECTED, borrower, loan_id),
+        (
+            rejected_by.clone(),
+            refunded_guarantee,
+            env.ledger().timestamp(),
+        ),
+    );
+}
+
+pub fn emit_risk_operator_changed(env: &Env, operator: &Address, allowed: bool) {
+    env.events()
+        .publish((RISK_OPERATOR_CHANGED, operator), allowed);
+}
+
+pub fn emit_asset_configured(env: &Env, asset: &Address, pool: &Address, enabled: bool) {
+    env.events()
+        .publish((ASSET_CONFIGURED, asset), (pool.clone(), enabled));
+}
+
 pub fn emit_loan_defaulted(
     env: &Env,
     borrower: Address,
@@ -92,10 +163,31 @@ pub fn emit_loan_repaid(
     );
 }
 
-pub fn emit_loan_cancelled(env: &Env, borrower: &Address, loan_id: u64, refunded_guarantee: i128) {
+pub fn emit_loan_cancelled(
+    env: &Env,
+    borrower: &Address,
+    loan_id: u64,
+    refunded_guarantee: i128,
+    reason: Symbol,
+) {
     env.events().publish(
         (LOAN_CANCELLED, borrower, loan_id),
-        (refunded_guarantee, env.ledger().timestamp()),
+        (refunded_guarantee, reason, env.ledger().timestamp()),
+    );
+}
+
+/// Emitted when a repayment closes an installment.  `index` is zero-based.
+pub fn emit_installment_paid(
+    env: &Env,
+    borrower: &Address,
+    loan_id: u64,
+    index: u32,
+    amount_paid: i128,
+    due_date: u64,
+) {
+    env.events().publish(
+        (INSTALLMENT_PAID, borrower, loan_id),
+        (index, amount_paid, due_date, env.ledger().timestamp()),
     );
 }
 
@@ -139,3 +231,210 @@ pub fn emit_loan_in_grace_period(
         ),
     );
 }
+
+/// Emitted when the admin or a risk operator offers a borrower a new schedule.
+pub fn emit_restructure_proposed(
+    env: &Env,
+    borrower: &Address,
+    loan_id: u64,
+    proposed_by: &Address,
+    restructure_fee: i128,
+    interest_adjustment: i128,
+) {
+    env.events().publish(
+        (RESTRUCTURE_PROPOSED, borrower, loan_id),
+        (
+            proposed_by.clone(),
+            restructure_fee,
+            interest_adjustment,
+            env.ledger().timestamp(),
+        ),
+    );
+}
+
+/// Emitted when the borrower accepts a restructuring and the new schedule
+/// takes effect.  `restructure_count` includes this restructuring.
+pub fn emit_loan_restructured(
+    env: &Env,
+    borrower: &Address,
+    loan_id: u64,
+    restructure_count: u32,
+    new_remaining_balance: i128,
+    installments: u32,
+) {
+    env.events().publish(
+        (LOAN_RESTRUCTURED, borrower, loan_id),
+        (
+            restructure_count,
+            new_remaining_balance,
+            installments,
+            env.ledger().timestamp(),
+        ),
+    );
+}
+
+/// Emitted when a payoff qualifies for the early-payoff rebate.  `rebate` is
+/// the unearned interest forgiven.
+pub fn emit_early_payoff_rebate(env: &Env, borrower: &Address, loan_id: u64, rebate: i128) {
+    env.events().publish(
+        (EARLY_PAYOFF_REBATE, borrower, loan_id),
+        (rebate, env.ledger().timestamp()),
+    );
+}
+
+/// Emitted alongside `LOANRPD` when a third party repays a loan through
+/// `repay_loan_for`.
+pub fn emit_sponsored_repayment(
+    env: &Env,
+    borrower: &Address,
+    loan_id: u64,
+    payer: &Address,
+    amount: i128,
+) {
+    env.events().publish(
+        (REPAYMENT_SPONSORED, borrower, loan_id),
+        (payer.clone(), amount, env.ledger().timestamp()),
+    );
+}
+
+/// Emitted once per `mark_defaulted_batch` call that defaulted at least one loan.
+pub fn emit_keeper_bounty_paid(env: &Env, keeper: &Address, loans_defaulted: u32, bounty: i128) {
+    env.events().publish(
+        (KEEPER_BOUNTY, keeper),
+        (loans_defaulted, bounty, env.ledger().timestamp()),
+    );
+}
+
+/// Emitted for each payment collected on a defaulted loan.  `settled` is true
+/// when the payment clears the recovery balance and the loan becomes `Recovered`.
+pub fn emit_recovery_payment(
+    env: &Env,
+    borrower: &Address,
+    loan_id: u64,
+    payer: &Address,
+    amount: i128,
+    still_owed: i128,
+    settled: bool,
+) {
+    env.events().publish(
+        (LOAN_RECOVERY, borrower, loan_id),
+        (
+            payer.clone(),
+            amount,
+            still_owed,
+            settled,
+            env.ledger().timestamp(),
+        ),
+    );
+}
+
+/// Emitted for each merchant refund.  `principal`, `interest` and `service_fee`
+/// are the amounts taken off the loan; `borrower_refund` is the part paid back
+/// to the borrower for principal they had already repaid; `fully_refunded` is
+/// true when the loan closes as `Refunded`.
+#[allow(clippy::too_many_arguments)]
+pub fn emit_merchant_refund(
+    env: &Env,
+    borrower: &Address,
+    loan_id: u64,
+    merchant: &Address,
+    principal: i128,
+    interest: i128,
+    service_fee: i128,
+    borrower_refund: i128,
+    new_balance: i128,
+    fully_refunded: bool,
+) {
+    env.events().publish(
+        (MERCHANT_REFUND, borrower, loan_id),
+        (
+            merchant.clone(),
+            principal,
+            interest,
+            service_fee,
+            borrower_refund,
+            new_balance,
+            fully_refunded,
+            env.ledger().timestamp(),
+        ),
+    );
+}
+
+/// Emitted when a loan's receivable is assigned to a new holder
+pub fn emit_receivable_transferred(
+    env: &Env,
+    borrower: &Address,
+    loan_id: u64,
+    from: &Address,
+    to: &Address,
+    principal_outstanding: i128,
+) {
+    env.events().publish(
+        (RECEIVABLE_TRANSFERRED, borrower, loan_id),
+        (
+            from.clone(),
+            to.clone(),
+            principal_outstanding,
+            env.ledger().timestamp(),
+        ),
+    );
+}
+
+/// Emitted when a borrower opens a revolving credit line
+pub fn emit_line_opened(env: &Env, borrower: &Address, credit_limit: i128, interest_rate_bps: u32) {
+    env.events().publish(
+        (LINE_OPENED, borrower),
+        (credit_limit, interest_rate_bps, env.ledger().timestamp()),
+    );
+}
+
+/// Emitted for each draw on a revolving credit line
+pub fn emit_line_drawn(
+    env: &Env,
+    borrower: &Address,
+    merchant: &Address,
+    amount: i128,
+    balance: i128,
+) {
+    env.events().publish(
+        (LINE_DRAWN, borrower),
+        (merchant.clone(), amount, balance, env.ledger().timestamp()),
+    );
+}
+
+/// Emitted for each repayment on a revolving credit line
+pub fn emit_line_repaid(
+    env: &Env,
+    borrower: &Address,
+    amount: i128,
+    interest_paid: i128,
+    balance: i128,
+) {
+    env.events().publish(
+        (LINE_REPAID, borrower),
+        (amount, interest_paid, balance, env.ledger().timestamp()),
+    );
+}
+
+/// Emitted when a billing cycle closes and its statement is stored
+pub fn emit_line_statement(env: &Env, borrower: &Address, statement: &RevolvingStatement) {
+    env.events().publish(
+        (LINE_STATEMENT, borrower, statement.cycle),
+        statement.clone(),
+    );
+}
+
+/// Emitted when a settled revolving credit line is closed
+pub fn emit_line_closed(env: &Env, borrower: &Address) {
+    env.events()
+        .publish((LINE_CLOSED, borrower), env.ledger().timestamp());
+}
+
+/// Emitted when a delinquent revolving credit line is defaulted. `principal`
+/// is the pool capital written off; `interest` the unpaid interest forgiven.
+pub fn emit_line_defaulted(env: &Env, borrower: &Address, principal: i128, interest: i128) {
+    env.events().publish(
+        (LINE_DEFAULTED, borrower),
+        (principal, interest, env.ledger().timestamp()),
+    );
+}
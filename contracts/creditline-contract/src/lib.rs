@@ -1,7 +1,9 @@
 #![no_std]
 use liquidity_pool_contract::LiquidityPoolContractClient;
 use merchant_registry_contract::MerchantRegistryContractClient;
-use parameters_contract::ProtocolParameters;
+use parameters_contract::{
+    CreditLimitTier, LateFeeBase, PricingModel, ProtocolParameters, DAYS_PER_YEAR,
+};
 use soroban_sdk::{
     auth::{ContractContext, InvokerContractAuthEntry, SubContractInvocation},
     contract, contractimpl, panic_with_error, symbol_short, token, Address, Env, IntoVal, Symbol,
@@ -15,7 +17,12 @@ mod storage;
 mod types;
 
 pub use errors::CreditLineError;
-pub use types::{default_protocol_parameters, Loan, LoanStatus, RepaymentInstallment};
+pub use types::{
+    default_credit_limit_tiers, default_pricing_model, default_protocol_parameters, AssetConfig,
+    InstallmentStatus, Loan, LoanAction, LoanHistoryEntry, LoanQuote, LoanStatus, PayoffQuote,
+    RepaymentInstallment, RestructureProposal, RevolvingLine, RevolvingLineStatus,
+    RevolvingStatement,
+};
 
 #[contract]
 pub struct CreditLineContract;
@@ -55,19 +62,132 @@ impl CreditLineContract {
         total_amount: i128,
         guarantee_amount: i128,
         repayment_schedule: Vec<RepaymentInstallment>,
+    ) -> u64 {
+        let asset = Self::default_asset(&env);
+        Self::create_loan_in_asset(
+            env,
+            user,
+            merchant,
+            asset,
+            total_amount,
+            guarantee_amount,
+            repayment_schedule,
+        )
+    }
+
+    /// Create and fund a loan denominated in `asset`, which must be the
+    /// default token or an enabled whitelisted asset. The guarantee is taken
+    /// and the merchant paid in that asset, from the asset's own pool.
+    pub fn create_loan_in_asset(
+        env: Env,
+        user: Address,
+        merchant: Address,
+        asset: Address,
+        total_amount: i128,
+        guarantee_amount: i128,
+        repayment_schedule: Vec<RepaymentInstallment>,
     ) -> u64 {
         user.require_auth();
 
+        Self::validate_asset(&env, &asset);
         Self::validate_guarantee(&env, total_amount, guarantee_amount);
         Self::validate_merchant(&env, &merchant);
         let score = Self::validate_reputation(&env, &user);
-        Self::validate_liquidity(&env, total_amount, guarantee_amount);
-        Self::enter_non_reentrant(&env);
+        Self::validate_liquidity(&env, &asset, total_amount, guarantee_amount);
 
-        let mut loan = Self::build_loan(
+        Self::open_loan(
+            &env,
+            user,
+            merchant,
+            asset,
+            total_amount,
+            guarantee_amount,
+            repayment_schedule,
+            score,
+        )
+    }
+
+    /// Create and fund a loan whose schedule is generated on-chain: the total
+    /// due (principal + interest + service fee) is split into
+    /// `installments_count` equal installments, one every `period_seconds`
+    /// starting one period from now. The last installment absorbs rounding dust.
+    pub fn create_loan_with_terms(
+        env: Env,
+        user: Address,
+        merchant: Address,
+        total_amount: i128,
+        guarantee_amount: i128,
+        installments_count: u32,
+        period_seconds: u64,
+    ) -> u64 {
+        user.require_auth();
+
+        let asset = Self::default_asset(&env);
+        Self::validate_guarantee(&env, total_amount, guarantee_amount);
+        Self::validate_merchant(&env, &merchant);
+        let score = Self::validate_reputation(&env, &user);
+        Self::validate_liquidity(&env, &asset, total_amount, guarantee_amount);
+
+        let quote = Self::build_quote(
+            &env,
+            total_amount,
+            score,
+            installments_count,
+            period_seconds,
+        );
+        Self::open_loan(
+            &env,
+            user,
+            merchant,
+            asset,
+            total_amount,
+            guarantee_amount,
+            quote.repayment_schedule,
+            score,
+        )
+    }
+
+    /// Price a prospective loan for `user` without writing any state. Returns
+    /// the interest rate, interest, service fee and the equal-installment
+    /// schedule `create_loan_with_terms` would generate right now.
+    pub fn quote_loan(
+        env: Env,
+        user: Address,
+        total_amount: i128,
+        installments_count: u32,
+        period_seconds: u64,
+    ) -> LoanQuote {
+        if total_amount <= 0 {
+            panic_with_error!(&env, CreditLineError::InvalidAmount);
+        }
+        let score = Self::validate_reputation(&env, &user);
+        Self::build_quote(
             &env,
+            total_amount,
+            score,
+            installments_count,
+            period_seconds,
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn open_loan(
+        env: &Env,
+        user: Address,
+        merchant: Address,
+        asset: Address,
+        total_amount: i128,
+        guarantee_amount: i128,
+        repayment_schedule: Vec<RepaymentInstallment>,
+        score: u32,
+    ) -> u64 {
+        Self::enter_non_reentrant(env);
+
+        let mut loan = Self::build_loan(
+            env,
             user.clone(),
             merchant.clone(),
+            asset.clone(),
             total_amount,
             guarantee_amount,
             repayment_schedule.clone(),
@@ -76,17 +196,24 @@ impl CreditLineContract {
         );
         loan.funded_at = env.ledger().timestamp();
 
-        storage::increase_user_active_debt(&env, &user, loan.remaining_balance);
+        storage::increase_user_active_debt(env, &user, &asset, loan.remaining_balance);
         let loan_id = loan.loan_id;
-        storage::write_loan(&env, &loan);
+        storage::write_loan(env, &loan);
 
         let pool_contribution = total_amount
             .checked_sub(guarantee_amount)
-            .unwrap_or_else(|| panic_with_error!(&env, CreditLineError::Underflow));
-        Self::fund_loan_from_pool(&env, &user, &merchant, guarantee_amount, pool_contribution);
+            .unwrap_or_else(|| panic_with_error!(env, CreditLineError::Underflow));
+        Self::fund_loan_from_pool(
+            env,
+            &asset,
+            &user,
+            &merchant,
+            guarantee_amount,
+            pool_contribution,
+        );
 
         events::emit_loan_created(
-            &env,
+            env,
             &user,
             &merchant,
             loan_id,
@@ -94,8 +221,15 @@ impl CreditLineContract {
             guarantee_amount,
             &repayment_schedule,
         );
+        Self::record_history(
+            env,
+            loan_id,
+            LoanAction::Created,
+            total_amount,
+            loan.remaining_balance,
+        );
 
-        Self::exit_non_reentrant(&env);
+        Self::exit_non_reentrant(env);
         loan_id
     }
 
@@ -106,15 +240,39 @@ impl CreditLineContract {
         total_amount: i128,
         guarantee_amount: i128,
         repayment_schedule: Vec<RepaymentInstallment>,
+    ) -> u64 {
+        let asset = Self::default_asset(&env);
+        Self::request_loan_in_asset(
+            env,
+            user,
+            merchant,
+            asset,
+            total_amount,
+            guarantee_amount,
+            repayment_schedule,
+        )
+    }
+
+    /// `request_loan` for a loan denominated in `asset`
+    pub fn request_loan_in_asset(
+        env: Env,
+        user: Address,
+        merchant: Address,
+        asset: Address,
+        total_amount: i128,
+        guarantee_amount: i128,
+        repayment_schedule: Vec<RepaymentInstallment>,
     ) -> u64 {
         user.require_auth();
 
+        Self::validate_asset(&env, &asset);
         Self::validate_guarantee(&env, total_amount, guarantee_amount);
         let score = Self::validate_reputation(&env, &user);
         let loan = Self::build_loan(
             &env,
             user.clone(),
             merchant.clone(),
+            asset.clone(),
             total_amount,
             guarantee_amount,
             repayment_schedule.clone(),
@@ -122,9 +280,7 @@ impl CreditLineContract {
             LoanStatus::Pending,
         );
 
-        let token_address = storage::get_token(&env)
-            .unwrap_or_else(|| panic_with_error!(&env, CreditLineError::TokenNotConfigured));
-        let token_client = token::Client::new(&env, &token_address);
+        let token_client = token::Client::new(&env, &asset);
         token_client.transfer(&user, &env.current_contract_address(), &guarantee_amount);
 
         let loan_id = loan.loan_id;
@@ -139,6 +295,13 @@ impl CreditLineContract {
             guarantee_amount,
             &repayment_schedule,
         );
+        Self::record_history(
+            &env,
+            loan_id,
+            LoanAction::Requested,
+            total_amount,
+            loan.remaining_balance,
+        );
 
         loan_id
     }
@@ -151,8 +314,103 @@ impl CreditLineContract {
         storage::get_user_loan_count(&env, &borrower)
     }
 
+    /// Loans currently in `status`, in the order they entered it. `start` and
+    /// `limit` are positions in the status's append-only index, not loan
+    /// counts: a loan keeps its position while it stays in `status` and a
+    /// loan leaving it vacates its position, so paging on from
+    /// `start + limit` never skips or repeats a loan even while statuses
+    /// change. A page may hold fewer than `limit` loans; the walk ends when
+    /// `start` reaches `get_status_index_length`.
+    pub fn get_loans_by_status(env: Env, status: LoanStatus, start: u64, limit: u32) -> Vec<Loan> {
+        storage::get_status_loans_paginated(&env, &status, start, limit)
+    }
+
+    /// Number of loans currently in `status`
+    pub fn get_loan_count_by_status(env: Env, status: LoanStatus) -> u64 {
+        storage::get_status_loan_count(&env, &status)
+    }
+
+    /// End of the status's index for `get_loans_by_status`, vacated
+    /// positions included
+    pub fn get_status_index_length(env: Env, status: LoanStatus) -> u64 {
+        storage::get_status_index_length(&env, &status)
+    }
+
+    /// Add loans written by the first release, which predate the merchant
+    /// and status indexes, to both. Walks loan ids `start..start + limit`
+    /// (ids start at 1); loans already indexed are left alone, so anyone may
+    /// call this. At most `MAX_INDEX_BATCH` ids are walked per call.
+    ///
+    /// Returns the number of loans added to the indexes.
+    pub fn index_loans(env: Env, start: u64, limit: u32) -> u32 {
+        if limit > types::MAX_INDEX_BATCH {
+            panic_with_error!(&env, CreditLineError::BatchTooLarge);
+        }
+
+        let first = start.max(1);
+        let end = first
+            .saturating_add(limit as u64)
+            .min(storage::get_loan_counter(&env).saturating_add(1));
+        let mut indexed = 0u32;
+        for loan_id in first..end {
+            if let Some(loan) = storage::read_loan(&env, loan_id) {
+                if storage::index_loan(&env, &loan) {
+                    indexed += 1;
+                }
+            }
+        }
+
+        indexed
+    }
+
+    /// Loans financed for `merchant`, in creation order. First-release loans
+    /// join when they are first written or indexed with `index_loans`.
+    pub fn get_merchant_loans(env: Env, merchant: Address, start: u64, limit: u32) -> Vec<Loan> {
+        storage::get_merchant_loans_paginated(&env, &merchant, start, limit)
+    }
+
+    pub fn get_merchant_loan_count(env: Env, merchant: Address) -> u64 {
+        storage::get_merchant_loan_count(&env, &merchant)
+    }
+
+    /// Active debt in the default token
     pub fn get_user_active_debt(env: Env, borrower: Address) -> i128 {
-        storage::get_user_active_debt(&env, &borrower)
+        let asset = Self::default_asset(&env);
+        storage::get_user_active_debt(&env, &borrower, &asset)
+    }
+
+    pub fn get_user_asset_debt(env: Env, borrower: Address, asset: Address) -> i128 {
+        storage::get_user_active_debt(&env, &borrower, &asset)
+    }
+
+    /// Maximum active debt the borrower may carry at their current reputation
+    /// score, per the governed credit-limit tiers.
+    pub fn get_credit_limit(env: Env, user: Address) -> i128 {
+        let score = Self::reputation_score(&env, &user);
+        Self::credit_limit(&env, score)
+    }
+
+    /// Remaining buying power in the default token: the credit limit less
+    /// current active debt across all assets, floored at zero.
+    pub fn get_available_credit(env: Env, user: Address) -> i128 {
+        let asset = Self::default_asset(&env);
+        Self::get_available_credit_in(env, user, asset)
+    }
+
+    /// Remaining buying power in `asset`: the headroom left under the single
+    /// credit limit shared by every asset, converted to `asset` units.
+    pub fn get_available_credit_in(env: Env, user: Address, asset: Address) -> i128 {
+        let limit = Self::get_credit_limit(env.clone(), user.clone());
+        let exposure = Self::total_exposure(&env, &user);
+        let headroom = limit.saturating_sub(exposure).max(0);
+        Self::from_default_units(&env, &asset, headroom)
+    }
+
+    /// Interest rate (bps of principal) the pricing model would charge `user`
+    /// on a loan running `tenor_seconds`.
+    pub fn get_rate_for(env: Env, user: Address, tenor_seconds: u64) -> u32 {
+        let score = Self::reputation_score(&env, &user);
+        Self::interest_rate_bps(&env, score, tenor_seconds)
     }
 
     pub fn get_loan(env: Env, loan_id: u64) -> Loan {
@@ -160,6 +418,14 @@ impl CreditLineContract {
             .unwrap_or_else(|| panic_with_error!(&env, CreditLineError::LoanNotFound))
     }
 
+    /// Return the loan's repayment schedule with installment statuses
+    /// evaluated at the current ledger time.
+    pub fn get_installments(env: Env, loan_id: u64) -> Vec<RepaymentInstallment> {
+        let loan = storage::read_loan(&env, loan_id)
+            .unwrap_or_else(|| panic_with_error!(&env, CreditLineError::LoanNotFound));
+        Self::refresh_installments(&env, &loan.repayment_schedule)
+    }
+
     pub fn set_admin(env: Env, new_admin: Address) {
         let old_admin = storage::get_admin(&env);
         old_admin.require_auth();
@@ -190,12 +456,85 @@ impl CreditLineContract {
         storage::set_liquidity_pool(&env, &address);
     }
 
+    /// Whitelist `asset` for new loans, funded by `pool` (a liquidity pool
+    /// denominated in that asset), or move an already whitelisted asset to a
+    /// new pool. The pool's token must be `asset`, and once a loan has been
+    /// made in the asset its pool can no longer change: open loans settle
+    /// through whichever pool the config names. The default token is
+    /// configured through `set_liquidity_pool`; naming it here fails with
+    /// `InvalidAssetPool` like any other pool rejection.
+    pub fn set_asset(env: Env, admin: Address, asset: Address, pool: Address) {
+        admin.require_auth();
+        access::require_admin(&env, &admin);
+        if storage::get_token(&env).as_ref() == Some(&asset) {
+            panic_with_error!(&env, CreditLineError::InvalidAssetPool);
+        }
+        if LiquidityPoolContractClient::new(&env, &pool).get_token() != asset {
+            panic_with_error!(&env, CreditLineError::InvalidAssetPool);
+        }
+        let decimals = token::Client::new(&env, &asset).decimals();
+
+        let has_loans = match storage::read_asset_config(&env, &asset) {
+            Some(existing) if existing.has_loans && existing.pool != pool => {
+                panic_with_error!(&env, CreditLineError::InvalidAssetPool)
+            }
+            Some(existing) => existing.has_loans,
+            None => false,
+        };
+        let config = AssetConfig {
+            pool: pool.clone(),
+            enabled: true,
+            has_loans,
+            decimals,
+        };
+        storage::write_asset_config(&env, &asset, &config);
+        events::emit_asset_configured(&env, &asset, &pool, true);
+    }
+
+    /// Stop new loans in `asset`. Open loans keep settling through its pool.
+    pub fn disable_asset(env: Env, admin: Address, asset: Address) {
+        admin.require_auth();
+        access::require_admin(&env, &admin);
+
+        let mut config = storage::read_asset_config(&env, &asset)
+            .unwrap_or_else(|| panic_with_error!(&env, CreditLineError::AssetNotSupported));
+        config.enabled = false;
+        storage::write_asset_config(&env, &asset, &config);
+        events::emit_asset_configured(&env, &asset, &config.pool, false);
+    }
+
+    pub fn get_asset_config(env: Env, asset: Address) -> Option<AssetConfig> {
+        storage::read_asset_config(&env, &asset)
+    }
+
+    /// Assets whitelisted with `set_asset`, including disabled ones. The
+    /// default token is not listed.
+    pub fn get_assets(env: Env) -> Vec<Address> {
+        storage::get_assets(&env)
+    }
+
+    pub fn is_asset_supported(env: Env, asset: Address) -> bool {
+        storage::get_token(&env).as_ref() == Some(&asset)
+            || storage::read_asset_config(&env, &asset).is_some_and(|config| config.enabled)
+    }
+
     pub fn set_parameters_contract(env: Env, admin: Address, address: Address) {
         admin.require_auth();
         access::require_admin(&env, &admin);
         storage::set_parameters_contract(&env, &address);
     }
 
+    pub fn set_risk_operator(env: Env, admin: Address, operator: Address, allowed: bool) {
+        admin.require_auth();
+        access::require_admin(&env, &admin);
+        storage::set_risk_operator(&env, &operator, allowed);
+        events::emit_risk_operator_changed(&env, &operator, allowed);
+    }
+
+    pub fn is_risk_operator(env: Env, operator: Address) -> bool {
+        storage::is_risk_operator(&env, &operator)
+    }
+
     fn validate_guarantee(env: &Env, total_amount: i128, guarantee_amount: i128) {
         if total_amount <= 0 || guarantee_amount <= 0 {
             panic_with_error!(env, CreditLineError::InvalidAmount);
@@ -207,7 +546,7 @@ impl CreditLineContract {
 
         let params = Self::get_protocol_parameters(env);
         let min_guarantee = total_amount
-            .checked_mul(params.min_guarantee_percent as i128)
+            .checked_mul(params.min_guarantee_percent)
             .and_then(|v| v.checked_div(100))
             .unwrap_or_else(|| panic_with_error!(env, CreditLineError::Overflow));
 
@@ -236,26 +575,66 @@ impl CreditLineContract {
     }
 
     fn validate_reputation(env: &Env, user: &Address) -> u32 {
+        let score = Self::reputation_score(env, user);
+
+        let params = Self::get_protocol_parameters(env);
+        if score < params.min_reputation_threshold {
+            panic_with_error!(env, CreditLineError::InsufficientReputation);
+        }
+
+        score
+    }
+
+    fn reputation_score(env: &Env, user: &Address) -> u32 {
         let reputation_contract = storage::get_reputation_contract(env)
             .unwrap_or_else(|| panic!("Reputation contract not configured"));
 
-        let score: u32 = env.invoke_contract(
+        env.invoke_contract(
             &reputation_contract,
             &symbol_short!("get_score"),
             (user,).into_val(env),
-        );
+        )
+    }
 
-        let params = Self::get_protocol_parameters(env);
-        if score < params.min_reputation_threshold {
-            panic_with_error!(env, CreditLineError::InsufficientReputation);
+    fn validate_asset(env: &Env, asset: &Address) {
+        if !Self::is_asset_supported(env.clone(), asset.clone()) {
+            panic_with_error!(env, CreditLineError::AssetNotSupported);
         }
+    }
 
-        score
+    /// The token single-asset deployments were initialised with
+    fn default_asset(env: &Env) -> Address {
+        storage::get_token(env)
+            .unwrap_or_else(|| panic_with_error!(env, CreditLineError::TokenNotConfigured))
+    }
+
+    /// Pool funding loans in `asset`: the configured liquidity pool for the
+    /// default token, the whitelisted pool otherwise
+    fn try_asset_pool(env: &Env, asset: &Address) -> Option<Address> {
+        if storage::get_token(env).as_ref() == Some(asset) {
+            storage::get_liquidity_pool(env)
+        } else {
+            storage::read_asset_config(env, asset).map(|config| config.pool)
+        }
+    }
+
+    fn asset_pool(env: &Env, asset: &Address) -> Address {
+        Self::try_asset_pool(env, asset)
+            .unwrap_or_else(|| panic_with_error!(env, CreditLineError::InsufficientLiquidity))
+    }
+
+    /// Pin a whitelisted asset to its current pool once a loan uses it
+    fn mark_asset_in_use(env: &Env, asset: &Address) {
+        if let Some(mut config) = storage::read_asset_config(env, asset) {
+            if !config.has_loans {
+                config.has_loans = true;
+                storage::write_asset_config(env, asset, &config);
+            }
+        }
     }
 
-    fn validate_liquidity(env: &Env, total_amount: i128, guarantee_amount: i128) {
-        let liquidity_pool = storage::get_liquidity_pool(env)
-            .unwrap_or_else(|| panic_with_error!(env, CreditLineError::InsufficientLiquidity));
+    fn validate_liquidity(env: &Env, asset: &Address, total_amount: i128, guarantee_amount: i128) {
+        let liquidity_pool = Self::asset_pool(env, asset);
 
         let required_from_pool = total_amount
             .checked_sub(guarantee_amount)
@@ -275,19 +654,22 @@ impl CreditLineContract {
 
     fn fund_loan_from_pool(
         env: &Env,
+        asset: &Address,
         borrower: &Address,
         merchant: &Address,
         guarantee_amount: i128,
         pool_contribution: i128,
     ) {
-        let liquidity_pool = storage::get_liquidity_pool(env)
-            .unwrap_or_else(|| panic_with_error!(env, CreditLineError::InsufficientLiquidity));
+        let token_client = token::Client::new(env, asset);
+        token_client.transfer(borrower, &env.current_contract_address(), &guarantee_amount);
 
-        let token_address = storage::get_token(env)
-            .unwrap_or_else(|| panic_with_error!(env, CreditLineError::TokenNotConfigured));
+        Self::disburse_from_pool(env, asset, merchant, pool_contribution);
+    }
 
-        let token_client = token::Client::new(env, &token_address);
-        token_client.transfer(borrower, &env.current_contract_address(), &guarantee_amount);
+    /// Ask the asset's liquidity pool to pay the merchant its share of the
+    /// purchase. The guarantee must already be held by this contract.
+    fn disburse_from_pool(env: &Env, asset: &Address, merchant: &Address, pool_contribution: i128) {
+        let liquidity_pool = Self::asset_pool(env, asset);
 
         if pool_contribution > 0 {
             let lp_client = LiquidityPoolContractClient::new(env, &liquidity_pool);
@@ -299,10 +681,12 @@ impl CreditLineContract {
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn build_loan(
         env: &Env,
         user: Address,
         merchant: Address,
+        asset: Address,
         total_amount: i128,
         guarantee_amount: i128,
         repayment_schedule: Vec<RepaymentInstallment>,
@@ -312,30 +696,24 @@ impl CreditLineContract {
         Self::validate_guarantee(env, total_amount, guarantee_amount);
         Self::validate_merchant(env, &merchant);
 
-        let interest_rate_bps = Self::interest_rate_bps(env, score);
-        let interest_amount =
-            Self::calculate_bps_amount(env, total_amount, interest_rate_bps as i128);
-        let service_fee_amount =
-            Self::calculate_bps_amount(env, total_amount, types::SERVICE_FEE_BPS);
-        let remaining_balance = total_amount
-            .checked_add(interest_amount)
-            .and_then(|v| v.checked_add(service_fee_amount))
-            .unwrap_or_else(|| panic_with_error!(env, CreditLineError::Overflow));
+        let tenor_seconds = repayment_schedule
+            .last()
+            .map(|i| i.due_date.saturating_sub(env.ledger().timestamp()))
+            .unwrap_or(0);
+        let (interest_rate_bps, interest_amount, service_fee_amount, remaining_balance) =
+            Self::price_loan(env, total_amount, score, tenor_seconds);
 
-        let credit_limit = Self::credit_limit(score);
-        let active_debt = storage::get_user_active_debt(env, &user);
-        let next_debt = active_debt
-            .checked_add(remaining_balance)
-            .unwrap_or_else(|| panic_with_error!(env, CreditLineError::Overflow));
-        if next_debt > credit_limit {
-            panic_with_error!(env, CreditLineError::ExposureLimitExceeded);
-        }
+        Self::validate_schedule(env, &repayment_schedule, remaining_balance);
+        Self::validate_exposure(env, &user, &asset, score, remaining_balance);
+        Self::mark_asset_in_use(env, &asset);
 
         let loan_id = storage::increment_loan_counter(env);
         Loan {
             loan_id,
             borrower: user,
             merchant,
+            asset,
+            beneficiary: None,
             total_amount,
             guarantee_amount,
             interest_rate_bps,
@@ -345,68 +723,372 @@ impl CreditLineContract {
             interest_outstanding: interest_amount,
             service_fee_outstanding: service_fee_amount,
             remaining_balance,
-            repayment_schedule,
+            repayment_schedule: Self::prepare_schedule(env, &repayment_schedule),
             status,
             created_at: env.ledger().timestamp(),
             funded_at: 0,
             late_fees_outstanding: 0,
             late_fee_accrual_timestamp: 0,
+            late_fees_accrued: 0,
+            restructure_count: 0,
+            recovered_amount: 0,
+            refunded_amount: 0,
         }
     }
 
-    fn calculate_bps_amount(env: &Env, base: i128, bps: i128) -> i128 {
-        base.checked_mul(bps)
-            .and_then(|v| v.checked_div(types::BPS_DENOMINATOR))
-            .unwrap_or_else(|| panic_with_error!(env, CreditLineError::Overflow))
+    /// Returns `(interest_rate_bps, interest_amount, service_fee_amount, total_due)`
+    /// for a loan of `total_amount` running `tenor_seconds` to a borrower with
+    /// the given score.
+    fn price_loan(
+        env: &Env,
+        total_amount: i128,
+        score: u32,
+        tenor_seconds: u64,
+    ) -> (u32, i128, i128, i128) {
+        let interest_rate_bps = Self::interest_rate_bps(env, score, tenor_seconds);
+        let interest_amount =
+            Self::calculate_bps_amount(env, total_amount, interest_rate_bps as i128);
+        let service_fee_amount =
+            Self::calculate_bps_amount(env, total_amount, types::SERVICE_FEE_BPS);
+        let total_due = total_amount
+            .checked_add(interest_amount)
+            .and_then(|v| v.checked_add(service_fee_amount))
+            .unwrap_or_else(|| panic_with_error!(env, CreditLineError::Overflow));
+
+        (
+            interest_rate_bps,
+            interest_amount,
+            service_fee_amount,
+            total_due,
+        )
     }
 
-    fn interest_rate_bps(env: &Env, score: u32) -> u32 {
-        let base_interest_bps = Self::get_protocol_parameters(env).base_interest_bps;
-        if base_interest_bps == 0 {
-            return match score {
-                90..=u32::MAX => 400,
-                75..=89 => 600,
-                60..=74 => 800,
-                _ => 1_000,
-            };
+    fn build_quote(
+        env: &Env,
+        total_amount: i128,
+        score: u32,
+        installments_count: u32,
+        period_seconds: u64,
+    ) -> LoanQuote {
+        let tenor_seconds = period_seconds.saturating_mul(installments_count as u64);
+        let (interest_rate_bps, interest_amount, service_fee_amount, total_due) =
+            Self::price_loan(env, total_amount, score, tenor_seconds);
+        let schedule = Self::generate_schedule(env, total_due, installments_count, period_seconds);
+        Self::validate_schedule(env, &schedule, total_due);
+
+        LoanQuote {
+            total_amount,
+            interest_rate_bps,
+            interest_amount,
+            service_fee_amount,
+            total_due,
+            repayment_schedule: Self::refresh_installments(env, &schedule),
         }
+    }
 
-        match score {
-            90..=u32::MAX => base_interest_bps.saturating_sub(600),
-            75..=89 => base_interest_bps.saturating_sub(400),
-            60..=74 => base_interest_bps.saturating_sub(200),
-            _ => base_interest_bps,
+    /// Split `total_due` into equal installments spaced `period_seconds` apart.
+    /// Integer division leaves up to `installments_count - 1` units of dust,
+    /// which are added to the final installment.
+    fn generate_schedule(
+        env: &Env,
+        total_due: i128,
+        installments_count: u32,
+        period_seconds: u64,
+    ) -> Vec<RepaymentInstallment> {
+        if installments_count == 0 || period_seconds == 0 {
+            panic_with_error!(env, CreditLineError::InvalidRepaymentTerms);
         }
-    }
 
-    fn credit_limit(score: u32) -> i128 {
-        match score {
-            90..=u32::MAX => 10_000,
-            75..=89 => 5_000,
-            60..=74 => 2_500,
-            _ => 1_000,
+        let base_amount = total_due / installments_count as i128;
+        let now = env.ledger().timestamp();
+        let mut schedule = Vec::new(env);
+        let mut allocated: i128 = 0;
+        for i in 1..=installments_count {
+            let due_date = period_seconds
+                .checked_mul(i as u64)
+                .and_then(|offset| now.checked_add(offset))
+                .unwrap_or_else(|| panic_with_error!(env, CreditLineError::Overflow));
+            let amount = if i == installments_count {
+                total_due - allocated
+            } else {
+                base_amount
+            };
+            allocated += amount;
+            schedule.push_back(RepaymentInstallment::new(due_date, amount));
         }
+        schedule
     }
 
-    fn calculate_default_penalty(env: &Env, loan: &Loan) -> u32 {
+    /// Reject schedules that cannot be serviced: the schedule must be non-empty,
+    /// have positive amounts and strictly increasing future due dates, respect
+    /// the governed installment count and tenor, and sum to `total_due`
+    /// (principal + interest + service fee).
+    fn validate_schedule(
+        env: &Env,
+        repayment_schedule: &Vec<RepaymentInstallment>,
+        total_due: i128,
+    ) {
+        if repayment_schedule.is_empty() {
+            panic_with_error!(env, CreditLineError::EmptyRepaymentSchedule);
+        }
+
         let params = Self::get_protocol_parameters(env);
-        if loan.total_amount > params.large_loan_threshold {
-            params.large_loan_default_penalty
-        } else {
-            params.default_penalty
+        if repayment_schedule.len() > params.max_installments {
+            panic_with_error!(env, CreditLineError::TooManyInstallments);
         }
-    }
 
-    /// Warn that a loan is past due but still within the grace period.
-    /// Emits a `LOANGRC` event so off-chain services and borrowers can be notified.
-    /// Returns `LoanNotOverdue` if the loan is not yet past its due date, and
-    /// `LoanNotActive` if the loan is not active.  Returns `Ok(())` when the
-    /// warning event was successfully emitted (i.e. the loan is in the grace window).
-    pub fn warn_grace_period(env: Env, loan_id: u64) -> Result<(), CreditLineError> {
-        let loan = storage::read_loan(&env, loan_id).ok_or(CreditLineError::LoanNotFound)?;
+        let now = env.ledger().timestamp();
+        let mut previous_due_date = now;
+        let mut scheduled_total: i128 = 0;
+        for installment in repayment_schedule.iter() {
+            if installment.amount <= 0 {
+                panic_with_error!(env, CreditLineError::InvalidInstallmentAmount);
+            }
+            if installment.due_date <= now {
+                panic_with_error!(env, CreditLineError::InstallmentDueDateInPast);
+            }
+            if installment.due_date <= previous_due_date {
+                panic_with_error!(env, CreditLineError::UnsortedRepaymentSchedule);
+            }
+            previous_due_date = installment.due_date;
+            scheduled_total = scheduled_total
+                .checked_add(installment.amount)
+                .unwrap_or_else(|| panic_with_error!(env, CreditLineError::Overflow));
+        }
 
-        if loan.status != LoanStatus::Active {
-            return Err(CreditLineError::LoanNotActive);
+        let max_due_date = now.saturating_add(params.max_tenor_seconds);
+        if previous_due_date > max_due_date {
+            panic_with_error!(env, CreditLineError::TenorTooLong);
+        }
+
+        if scheduled_total != total_due {
+            panic_with_error!(env, CreditLineError::ScheduleAmountMismatch);
+        }
+    }
+
+    /// Copy the caller-supplied schedule, discarding any accounting fields so a
+    /// loan always starts with every installment unpaid.
+    fn prepare_schedule(
+        env: &Env,
+        repayment_schedule: &Vec<RepaymentInstallment>,
+    ) -> Vec<RepaymentInstallment> {
+        let mut schedule = Vec::new(env);
+        for installment in repayment_schedule.iter() {
+            schedule.push_back(RepaymentInstallment::new(
+                installment.due_date,
+                installment.amount,
+            ));
+        }
+        Self::refresh_installments(env, &schedule)
+    }
+
+    /// Recompute each open installment's status at the current ledger time.
+    /// The oldest open installment that is not yet overdue is `Due`; later ones
+    /// are `Upcoming`.
+    fn refresh_installments(
+        env: &Env,
+        repayment_schedule: &Vec<RepaymentInstallment>,
+    ) -> Vec<RepaymentInstallment> {
+        let now = env.ledger().timestamp();
+        let mut next_due_assigned = false;
+        let mut schedule = Vec::new(env);
+
+        for mut installment in repayment_schedule.iter() {
+            if !installment.is_paid() {
+                installment.status = if installment.due_date < now {
+                    InstallmentStatus::Late
+                } else if !next_due_assigned {
+                    next_due_assigned = true;
+                    InstallmentStatus::Due
+                } else {
+                    InstallmentStatus::Upcoming
+                };
+            }
+            schedule.push_back(installment);
+        }
+
+        schedule
+    }
+
+    /// Apply `amount` to open installments, oldest first.  When `settle_all` is
+    /// set (the loan balance reached zero) every remaining open installment is
+    /// closed as well.  Emits `LOANINST` for each installment that becomes paid.
+    fn apply_payment_to_installments(env: &Env, loan: &mut Loan, amount: i128, settle_all: bool) {
+        let now = env.ledger().timestamp();
+        let mut unapplied = amount;
+        let mut schedule = Vec::new(env);
+
+        for (index, mut installment) in (0u32..).zip(loan.repayment_schedule.iter()) {
+            if !installment.is_paid() {
+                let applied = unapplied.min(installment.outstanding());
+                installment.amount_paid = installment
+                    .amount_paid
+                    .checked_add(applied)
+                    .unwrap_or_else(|| panic_with_error!(env, CreditLineError::Overflow));
+                unapplied = unapplied
+                    .checked_sub(applied)
+                    .unwrap_or_else(|| panic_with_error!(env, CreditLineError::Underflow));
+
+                if settle_all {
+                    installment.amount_paid = installment.amount;
+                }
+
+                if installment.outstanding() <= 0 {
+                    installment.status = InstallmentStatus::Paid;
+                    installment.paid_at = now;
+                    events::emit_installment_paid(
+                        env,
+                        &loan.borrower,
+                        loan.loan_id,
+                        index,
+                        installment.amount_paid,
+                        installment.due_date,
+                    );
+                }
+            }
+            schedule.push_back(installment);
+        }
+
+        loan.repayment_schedule = Self::refresh_installments(env, &schedule);
+    }
+
+    /// The credit limit caps the borrower's debt in all assets together,
+    /// measured in default-token units.
+    fn validate_exposure(
+        env: &Env,
+        user: &Address,
+        asset: &Address,
+        score: u32,
+        additional_debt: i128,
+    ) {
+        let credit_limit = Self::credit_limit(env, score);
+        let next_debt = Self::total_exposure(env, user)
+            .checked_add(Self::to_default_units(env, asset, additional_debt))
+            .unwrap_or_else(|| panic_with_error!(env, CreditLineError::Overflow));
+        if next_debt > credit_limit {
+            panic_with_error!(env, CreditLineError::ExposureLimitExceeded);
+        }
+    }
+
+    /// Active debt in the default token plus every whitelisted asset's,
+    /// in default-token units
+    fn total_exposure(env: &Env, user: &Address) -> i128 {
+        let default_asset = Self::default_asset(env);
+        let mut exposure = storage::get_user_active_debt(env, user, &default_asset);
+        for asset in storage::get_assets(env).iter() {
+            let debt = storage::get_user_active_debt(env, user, &asset);
+            if debt > 0 {
+                exposure = exposure
+                    .checked_add(Self::to_default_units(env, &asset, debt))
+                    .unwrap_or_else(|| panic_with_error!(env, CreditLineError::Overflow));
+            }
+        }
+        exposure
+    }
+
+    /// `(asset_units, default_units)`: that many base units of `asset` are
+    /// worth that many of the default token. Whitelisted assets are
+    /// stablecoins, so only their decimals differ; the default token and
+    /// unlisted assets convert one to one.
+    fn unit_ratio(env: &Env, asset: &Address) -> (i128, i128) {
+        let Some(config) = storage::read_asset_config(env, asset) else {
+            return (1, 1);
+        };
+        let default_decimals = token::Client::new(env, &Self::default_asset(env)).decimals();
+        let factor = |exponent: u32| {
+            10i128
+                .checked_pow(exponent)
+                .unwrap_or_else(|| panic_with_error!(env, CreditLineError::Overflow))
+        };
+        if config.decimals >= default_decimals {
+            (factor(config.decimals - default_decimals), 1)
+        } else {
+            (1, factor(default_decimals - config.decimals))
+        }
+    }
+
+    /// `amount` of `asset` in default-token units, rounded up so debt is
+    /// never understated
+    fn to_default_units(env: &Env, asset: &Address, amount: i128) -> i128 {
+        let (asset_units, default_units) = Self::unit_ratio(env, asset);
+        amount
+            .checked_mul(default_units)
+            .and_then(|v| v.checked_add(asset_units - 1))
+            .and_then(|v| v.checked_div(asset_units))
+            .unwrap_or_else(|| panic_with_error!(env, CreditLineError::Overflow))
+    }
+
+    /// `amount` of the default token in `asset` units, rounded down
+    fn from_default_units(env: &Env, asset: &Address, amount: i128) -> i128 {
+        let (asset_units, default_units) = Self::unit_ratio(env, asset);
+        amount
+            .checked_mul(asset_units)
+            .and_then(|v| v.checked_div(default_units))
+            .unwrap_or_else(|| panic_with_error!(env, CreditLineError::Overflow))
+    }
+
+    fn calculate_bps_amount(env: &Env, base: i128, bps: i128) -> i128 {
+        base.checked_mul(bps)
+            .and_then(|v| v.checked_div(types::BPS_DENOMINATOR))
+            .unwrap_or_else(|| panic_with_error!(env, CreditLineError::Overflow))
+    }
+
+    /// Rate of the highest score tier the score reaches (or `max_rate_bps` when
+    /// it reaches none), plus the premium of the longest tenor bucket the loan
+    /// reaches, clamped to the model's bounds.
+    fn interest_rate_bps(env: &Env, score: u32, tenor_seconds: u64) -> u32 {
+        let model = Self::get_pricing_model(env);
+
+        let mut rate_bps = model.max_rate_bps;
+        for tier in model.rate_tiers.iter() {
+            if score >= tier.min_score {
+                rate_bps = tier.rate_bps;
+            }
+        }
+
+        let mut premium_bps = 0;
+        for bucket in model.tenor_premiums.iter() {
+            if tenor_seconds >= bucket.min_tenor_seconds {
+                premium_bps = bucket.premium_bps;
+            }
+        }
+
+        rate_bps
+            .saturating_add(premium_bps)
+            .clamp(model.min_rate_bps, model.max_rate_bps)
+    }
+
+    /// Maximum exposure of the highest tier whose `min_score` the score reaches;
+    /// zero when the score is below every tier.
+    fn credit_limit(env: &Env, score: u32) -> i128 {
+        let mut limit = 0;
+        for tier in Self::get_credit_limit_tiers(env).iter() {
+            if score >= tier.min_score {
+                limit = tier.max_exposure;
+            }
+        }
+        limit
+    }
+
+    fn calculate_default_penalty(env: &Env, loan: &Loan) -> u32 {
+        let params = Self::get_protocol_parameters(env);
+        if loan.total_amount > params.large_loan_threshold {
+            params.large_loan_default_penalty
+        } else {
+            params.default_penalty
+        }
+    }
+
+    /// Warn that a loan is past due but still within the grace period.
+    /// Emits a `LOANGRC` event so off-chain services and borrowers can be notified.
+    /// Returns `LoanNotOverdue` if the loan is not yet past its due date, and
+    /// `LoanNotActive` if the loan is not active.  Returns `Ok(())` when the
+    /// warning event was successfully emitted (i.e. the loan is in the grace window).
+    pub fn warn_grace_period(env: Env, loan_id: u64) -> Result<(), CreditLineError> {
+        let loan = storage::read_loan(&env, loan_id).ok_or(CreditLineError::LoanNotFound)?;
+
+        if loan.status != LoanStatus::Active {
+            return Err(CreditLineError::LoanNotActive);
         }
 
         let last_installment = loan
@@ -437,210 +1119,1570 @@ impl CreditLineContract {
             loan.remaining_balance,
             grace_ends_at,
         );
+        Self::record_history(
+            &env,
+            loan_id,
+            LoanAction::GraceWarning,
+            0,
+            loan.remaining_balance,
+        );
+
+        Ok(())
+    }
+
+    pub fn mark_defaulted(env: Env, loan_id: u64) -> Result<(), CreditLineError> {
+        let mut loan = storage::read_loan(&env, loan_id).ok_or(CreditLineError::LoanNotFound)?;
+
+        let grace_ends_at = Self::grace_period_end(&env, &loan)?;
+        if env.ledger().timestamp() <= grace_ends_at {
+            // Still within the grace window — emit a warning and block hard default.
+            events::emit_loan_in_grace_period(
+                &env,
+                &loan.borrower,
+                loan_id,
+                loan.remaining_balance,
+                grace_ends_at,
+            );
+            return Err(CreditLineError::LoanInGracePeriod);
+        }
 
+        Self::try_asset_pool(&env, &loan.asset).ok_or(CreditLineError::InsufficientLiquidity)?;
+
+        Self::enter_non_reentrant(&env);
+        Self::execute_default(&env, &mut loan, 0);
+        Self::exit_non_reentrant(&env);
         Ok(())
     }
 
-    pub fn mark_defaulted(env: Env, loan_id: u64) -> Result<(), CreditLineError> {
-        let mut loan = storage::read_loan(&env, loan_id).ok_or(CreditLineError::LoanNotFound)?;
+    /// Default every eligible loan in `loan_ids` and pay `keeper` a bounty of
+    /// `keeper_bounty_bps` of each forfeited guarantee, in the loan's asset; the
+    /// rest of the guarantee goes to the pool as usual. Loans that are not (yet)
+    /// defaultable are skipped rather than failing the batch. At most
+    /// `MAX_DEFAULT_BATCH` ids are accepted per call.
+    ///
+    /// Returns the number of loans defaulted in this call.
+    pub fn mark_defaulted_batch(env: Env, keeper: Address, loan_ids: Vec<u64>) -> u32 {
+        keeper.require_auth();
+        if loan_ids.len() > types::MAX_DEFAULT_BATCH {
+            panic_with_error!(&env, CreditLineError::BatchTooLarge);
+        }
+
+        let bounty_bps = Self::get_protocol_parameters(&env).keeper_bounty_bps as i128;
+
+        Self::enter_non_reentrant(&env);
+
+        let mut defaulted = 0u32;
+        let mut total_bounty: i128 = 0;
+        for loan_id in loan_ids.iter() {
+            let mut loan = match storage::read_loan(&env, loan_id) {
+                Some(loan) if Self::is_defaultable(&env, &loan) => loan,
+                _ => continue,
+            };
+
+            let bounty = Self::calculate_bps_amount(&env, loan.guarantee_amount, bounty_bps);
+            Self::execute_default(&env, &mut loan, bounty);
+            if bounty > 0 {
+                let token_client = token::Client::new(&env, &loan.asset);
+                token_client.transfer(&env.current_contract_address(), &keeper, &bounty);
+            }
+            total_bounty = total_bounty
+                .checked_add(bounty)
+                .unwrap_or_else(|| panic_with_error!(&env, CreditLineError::Overflow));
+            defaulted += 1;
+        }
+
+        if defaulted > 0 {
+            events::emit_keeper_bounty_paid(&env, &keeper, defaulted, total_bounty);
+        }
+
+        Self::exit_non_reentrant(&env);
+        defaulted
+    }
+
+    /// Loan ids in `start..start + limit` that `mark_defaulted` would accept
+    /// right now: active, past the final due date and past the grace period.
+    pub fn get_defaultable_loans(env: Env, start: u64, limit: u32) -> Vec<u64> {
+        let loan_count = storage::get_loan_counter(&env);
+        let mut defaultable = Vec::new(&env);
+
+        let first = start.max(1);
+        let end = first
+            .saturating_add(limit as u64)
+            .min(loan_count.saturating_add(1));
+        let mut loan_id = first;
+        while loan_id < end {
+            if let Some(loan) = storage::read_loan(&env, loan_id) {
+                if Self::is_defaultable(&env, &loan) {
+                    defaultable.push_back(loan_id);
+                }
+            }
+            loan_id += 1;
+        }
+
+        defaultable
+    }
+
+    /// Assign an active loan's future cash flows to `to`. The current holder
+    /// authorises: the asset's pool while the loan is unassigned, the last
+    /// buyer afterwards. From then on repayments, refunds, the guarantee on
+    /// default and recovery payments go to `to` instead of the pool. A
+    /// receivable cannot be assigned back to the pool.
+    ///
+    /// Returns the pool capital still outstanding in the loan (principal not
+    /// covered by the guarantee), which the pool releases when it sells.
+    pub fn transfer_receivable(env: Env, loan_id: u64, to: Address) -> i128 {
+        let mut loan = storage::read_loan(&env, loan_id)
+            .unwrap_or_else(|| panic_with_error!(&env, CreditLineError::LoanNotFound));
+
+        if loan.status != LoanStatus::Active {
+            panic_with_error!(&env, CreditLineError::LoanNotActive);
+        }
+
+        let pool = Self::asset_pool(&env, &loan.asset);
+        let holder = loan.beneficiary.clone().unwrap_or_else(|| pool.clone());
+        holder.require_auth();
+
+        if to == pool || to == holder || to == env.current_contract_address() {
+            panic_with_error!(&env, CreditLineError::InvalidBeneficiary);
+        }
+
+        loan.beneficiary = Some(to.clone());
+        storage::write_loan(&env, &loan);
+
+        let pool_capital = Self::pool_capital(&loan);
+        events::emit_receivable_transferred(
+            &env,
+            &loan.borrower,
+            loan_id,
+            &holder,
+            &to,
+            loan.principal_outstanding,
+        );
+        Self::record_history(
+            &env,
+            loan_id,
+            LoanAction::ReceivableTransferred,
+            pool_capital,
+            loan.remaining_balance,
+        );
+
+        pool_capital
+    }
+
+    /// Current holder of a loan's cash flows: the assigned beneficiary, or the
+    /// pool of the loan's asset.
+    pub fn get_beneficiary(env: Env, loan_id: u64) -> Address {
+        let loan = storage::read_loan(&env, loan_id)
+            .unwrap_or_else(|| panic_with_error!(&env, CreditLineError::LoanNotFound));
+        loan.beneficiary
+            .unwrap_or_else(|| Self::asset_pool(&env, &loan.asset))
+    }
+
+    /// Refund part or all of a financed purchase, e.g. when goods are returned.
+    /// The loan's merchant pays back `amount`, at most what it was paid for the
+    /// purchase (the principal beyond the guarantee) less earlier refunds. The
+    /// refund goes to the pool first, as returned principal, up to the pool
+    /// capital still lent out in the loan; it comes off the outstanding
+    /// principal together with the same share of the outstanding interest and
+    /// service fee. Anything beyond the pool capital is principal the borrower
+    /// already repaid and goes straight back to them. Refunding the whole
+    /// purchase price reverses the purchase: the rest of the principal,
+    /// interest and fee is cancelled too. Late fees are not refunded. If
+    /// nothing remains owed the loan closes as `Refunded` and the guarantee
+    /// goes back to the borrower.
+    ///
+    /// Returns the new loan balance.
+    pub fn merchant_refund(env: Env, merchant: Address, loan_id: u64, amount: i128) -> i128 {
+        merchant.require_auth();
+
+        let mut loan = storage::read_loan(&env, loan_id)
+            .unwrap_or_else(|| panic_with_error!(&env, CreditLineError::LoanNotFound));
+
+        if loan.status != LoanStatus::Active {
+            panic_with_error!(&env, CreditLineError::LoanNotActive);
+        }
+        if loan.merchant != merchant {
+            panic_with_error!(&env, CreditLineError::UnauthorizedMerchant);
+        }
+        if amount <= 0 {
+            panic_with_error!(&env, CreditLineError::InvalidAmount);
+        }
+        let refundable = loan
+            .total_amount
+            .checked_sub(loan.guarantee_amount)
+            .and_then(|v| v.checked_sub(loan.refunded_amount))
+            .unwrap_or_else(|| panic_with_error!(&env, CreditLineError::Underflow));
+        if amount > refundable {
+            panic_with_error!(&env, CreditLineError::RefundExceedsPurchasePrice);
+        }
+        let pool_refund = amount.min(Self::pool_capital(&loan));
+        let borrower_refund = amount
+            .checked_sub(pool_refund)
+            .unwrap_or_else(|| panic_with_error!(&env, CreditLineError::Underflow));
+
+        let lp_address = Self::asset_pool(&env, &loan.asset);
+        let token_address = loan.asset.clone();
+
+        Self::enter_non_reentrant(&env);
+
+        let accrued_fee = Self::accrue_late_fees_internal(&env, &mut loan);
+        if accrued_fee > 0 {
+            storage::increase_user_active_debt(&env, &loan.borrower, &loan.asset, accrued_fee);
+            events::emit_late_fee_accrued(
+                &env,
+                &loan.borrower,
+                loan_id,
+                accrued_fee,
+                loan.remaining_balance,
+            );
+            Self::record_history(
+                &env,
+                loan_id,
+                LoanAction::LateFeeAccrued,
+                accrued_fee,
+                loan.remaining_balance,
+            );
+        }
+
+        let (principal_refund, interest_refund, fee_refund) = if amount == refundable {
+            (
+                loan.principal_outstanding,
+                loan.interest_outstanding,
+                loan.service_fee_outstanding,
+            )
+        } else {
+            (
+                pool_refund,
+                Self::pro_rata(
+                    &env,
+                    loan.interest_outstanding,
+                    pool_refund,
+                    loan.principal_outstanding,
+                ),
+                Self::pro_rata(
+                    &env,
+                    loan.service_fee_outstanding,
+                    pool_refund,
+                    loan.principal_outstanding,
+                ),
+            )
+        };
+        let reduction = principal_refund
+            .checked_add(interest_refund)
+            .and_then(|v| v.checked_add(fee_refund))
+            .unwrap_or_else(|| panic_with_error!(&env, CreditLineError::Overflow));
+
+        loan.principal_outstanding = loan
+            .principal_outstanding
+            .checked_sub(principal_refund)
+            .unwrap_or_else(|| panic_with_error!(&env, CreditLineError::Underflow));
+        loan.interest_outstanding = loan
+            .interest_outstanding
+            .checked_sub(interest_refund)
+            .unwrap_or_else(|| panic_with_error!(&env, CreditLineError::Underflow));
+        loan.service_fee_outstanding = loan
+            .service_fee_outstanding
+            .checked_sub(fee_refund)
+            .unwrap_or_else(|| panic_with_error!(&env, CreditLineError::Underflow));
+        loan.remaining_balance = loan
+            .remaining_balance
+            .checked_sub(reduction)
+            .unwrap_or_else(|| panic_with_error!(&env, CreditLineError::Underflow));
+        loan.refunded_amount = loan
+            .refunded_amount
+            .checked_add(amount)
+            .unwrap_or_else(|| panic_with_error!(&env, CreditLineError::Overflow));
+
+        let fully_refunded = loan.remaining_balance == 0;
+        if fully_refunded {
+            loan.status = LoanStatus::Refunded;
+            storage::remove_restructure_proposal(&env, loan.loan_id);
+        }
+        Self::reduce_installments_from_end(&env, &mut loan, reduction, fully_refunded);
+
+        storage::decrease_user_active_debt(&env, &loan.borrower, &loan.asset, reduction);
+        storage::write_loan(&env, &loan);
+
+        let token_client = token::Client::new(&env, &token_address);
+        if pool_refund > 0 {
+            if let Some(holder) = &loan.beneficiary {
+                token_client.transfer(&merchant, holder, &pool_refund);
+            } else {
+                token_client.transfer(&merchant, &env.current_contract_address(), &pool_refund);
+                Self::authorize_token_transfer(&env, &token_address, &lp_address, pool_refund);
+                let lp_client = LiquidityPoolContractClient::new(&env, &lp_address);
+                lp_client.receive_repayment(&env.current_contract_address(), &pool_refund, &0);
+            }
+        }
+        if borrower_refund > 0 {
+            token_client.transfer(&merchant, &loan.borrower, &borrower_refund);
+        }
+
+        if fully_refunded {
+            token_client.transfer(
+                &env.current_contract_address(),
+                &loan.borrower,
+                &loan.guarantee_amount,
+            );
+        }
+
+        events::emit_merchant_refund(
+            &env,
+            &loan.borrower,
+            loan_id,
+            &merchant,
+            principal_refund,
+            interest_refund,
+            fee_refund,
+            borrower_refund,
+            loan.remaining_balance,
+            fully_refunded,
+        );
+        Self::record_history(
+            &env,
+            loan_id,
+            LoanAction::MerchantRefund,
+            amount,
+            loan.remaining_balance,
+        );
+
+        Self::exit_non_reentrant(&env);
+        loan.remaining_balance
+    }
+
+    /// `value × part / whole`, rounded down.
+    fn pro_rata(env: &Env, value: i128, part: i128, whole: i128) -> i128 {
+        if whole == 0 {
+            return 0;
+        }
+        value
+            .checked_mul(part)
+            .and_then(|v| v.checked_div(whole))
+            .unwrap_or_else(|| panic_with_error!(env, CreditLineError::Overflow))
+    }
+
+    /// Shrink open installments by `reduction`, latest first, so a refund
+    /// shortens the schedule rather than prepaying it.  Installments reduced to
+    /// what has already been paid on them are closed; `settle_all` closes every
+    /// remaining one.
+    fn reduce_installments_from_end(env: &Env, loan: &mut Loan, reduction: i128, settle_all: bool) {
+        let now = env.ledger().timestamp();
+        let mut remaining = reduction;
+        let mut schedule = loan.repayment_schedule.clone();
+
+        for index in (0..schedule.len()).rev() {
+            let mut installment = schedule.get_unchecked(index);
+            if installment.is_paid() {
+                continue;
+            }
+            let cut = remaining.min(installment.outstanding());
+            installment.amount = installment
+                .amount
+                .checked_sub(cut)
+                .unwrap_or_else(|| panic_with_error!(env, CreditLineError::Underflow));
+            remaining = remaining
+                .checked_sub(cut)
+                .unwrap_or_else(|| panic_with_error!(env, CreditLineError::Underflow));
+
+            if settle_all || installment.outstanding() <= 0 {
+                installment.amount = installment.amount_paid;
+                installment.status = InstallmentStatus::Paid;
+                installment.paid_at = now;
+            }
+            schedule.set(index, installment);
+        }
+
+        loan.repayment_schedule = Self::refresh_installments(env, &schedule);
+    }
+
+    /// Open a revolving credit line for `user`. The line's limit is the credit
+    /// limit for the user's reputation score, and its annual interest rate is
+    /// what the pricing model charges that score on a one-year loan. A closed
+    /// line may be reopened; statement numbering carries on. A defaulted line
+    /// may not.
+    ///
+    /// Returns the approved limit.
+    pub fn open_revolving_line(env: Env, user: Address) -> i128 {
+        user.require_auth();
+
+        let existing = storage::read_revolving_line(&env, &user);
+        if let Some(line) = &existing {
+            match line.status {
+                RevolvingLineStatus::Open => {
+                    panic_with_error!(&env, CreditLineError::RevolvingLineExists)
+                }
+                RevolvingLineStatus::Defaulted => {
+                    panic_with_error!(&env, CreditLineError::RevolvingLineDelinquent)
+                }
+                RevolvingLineStatus::Closed => {}
+            }
+        }
+
+        let score = Self::validate_reputation(&env, &user);
+        let credit_limit = Self::credit_limit(&env, score);
+        let interest_rate_bps = Self::interest_rate_bps(&env, score, types::SECONDS_PER_YEAR);
+        let now = env.ledger().timestamp();
+
+        let line = RevolvingLine {
+            borrower: user.clone(),
+            credit_limit,
+            interest_rate_bps,
+            principal_outstanding: 0,
+            interest_outstanding: 0,
+            status: RevolvingLineStatus::Open,
+            opened_at: now,
+            last_accrual_at: now,
+            cycle: existing.map(|line| line.cycle).unwrap_or(1),
+            cycle_start: now,
+            cycle_opening_balance: 0,
+            cycle_drawn: 0,
+            cycle_repaid: 0,
+            cycle_interest: 0,
+            minimum_due: 0,
+            past_due_since: 0,
+        };
+        storage::write_revolving_line(&env, &line);
+        events::emit_line_opened(&env, &user, credit_limit, interest_rate_bps);
+
+        credit_limit
+    }
+
+    /// Draw `amount` on the user's revolving line to pay `merchant`. The pool
+    /// funds the merchant directly; the draw counts toward `UserActiveDebt` and
+    /// must fit within the credit limit for the user's current score. A line
+    /// with a missed minimum payment cannot draw until it is caught up.
+    ///
+    /// Returns the line balance (principal + accrued interest) after the draw.
+    pub fn draw(env: Env, user: Address, merchant: Address, amount: i128) -> i128 {
+        user.require_auth();
+
+        if amount <= 0 {
+            panic_with_error!(&env, CreditLineError::InvalidAmount);
+        }
+
+        let mut line = Self::read_open_line(&env, &user);
+        Self::validate_merchant(&env, &merchant);
+        let score = Self::validate_reputation(&env, &user);
+        Self::settle_revolving_line(&env, &mut line);
+        if line.past_due_since != 0 {
+            panic_with_error!(&env, CreditLineError::RevolvingLineDelinquent);
+        }
+        line.credit_limit = Self::credit_limit(&env, score);
+        let asset = Self::default_asset(&env);
+        Self::validate_exposure(&env, &user, &asset, score, amount);
+        Self::validate_liquidity(&env, &asset, amount, 0);
+
+        Self::enter_non_reentrant(&env);
+
+        line.principal_outstanding = line
+            .principal_outstanding
+            .checked_add(amount)
+            .unwrap_or_else(|| panic_with_error!(&env, CreditLineError::Overflow));
+        line.cycle_drawn = line
+            .cycle_drawn
+            .checked_add(amount)
+            .unwrap_or_else(|| panic_with_error!(&env, CreditLineError::Overflow));
+        storage::increase_user_active_debt(&env, &user, &asset, amount);
+        storage::write_revolving_line(&env, &line);
+
+        Self::disburse_from_pool(&env, &asset, &merchant, amount);
+
+        let balance = Self::revolving_balance(&line);
+        events::emit_line_drawn(&env, &user, &merchant, amount, balance);
+
+        Self::exit_non_reentrant(&env);
+        balance
+    }
+
+    /// Repay any amount up to the line balance. Accrued interest is paid first,
+    /// then drawn principal; both go to the pool. Payments count toward the
+    /// minimum due on the last statement; covering it brings a past-due line
+    /// current.
+    ///
+    /// Returns the remaining line balance.
+    pub fn repay_line(env: Env, borrower: Address, amount: i128) -> i128 {
+        borrower.require_auth();
+
+        let mut line = Self::read_open_line(&env, &borrower);
+        Self::settle_revolving_line(&env, &mut line);
+
+        if amount <= 0 || amount > Self::revolving_balance(&line) {
+            panic_with_error!(&env, CreditLineError::InvalidRepaymentAmount);
+        }
+
+        let token_address = Self::default_asset(&env);
+        let lp_address = Self::asset_pool(&env, &token_address);
+
+        Self::enter_non_reentrant(&env);
+
+        let interest_paid = amount.min(line.interest_outstanding);
+        let principal_paid = amount
+            .checked_sub(interest_paid)
+            .unwrap_or_else(|| panic_with_error!(&env, CreditLineError::Underflow));
+        line.interest_outstanding = line
+            .interest_outstanding
+            .checked_sub(interest_paid)
+            .unwrap_or_else(|| panic_with_error!(&env, CreditLineError::Underflow));
+        line.principal_outstanding = line
+            .principal_outstanding
+            .checked_sub(principal_paid)
+            .unwrap_or_else(|| panic_with_error!(&env, CreditLineError::Underflow));
+        line.minimum_due = line.minimum_due.saturating_sub(amount).max(0);
+        if line.minimum_due == 0 {
+            line.past_due_since = 0;
+        }
+        line.cycle_repaid = line
+            .cycle_repaid
+            .checked_add(amount)
+            .unwrap_or_else(|| panic_with_error!(&env, CreditLineError::Overflow));
+        storage::decrease_user_active_debt(&env, &borrower, &token_address, amount);
+        storage::write_revolving_line(&env, &line);
+
+        let token_client = token::Client::new(&env, &token_address);
+        token_client.transfer(&borrower, &env.current_contract_address(), &amount);
+        Self::authorize_token_transfer(&env, &token_address, &lp_address, amount);
+        let lp_client = LiquidityPoolContractClient::new(&env, &lp_address);
+        lp_client.receive_repayment(
+            &env.current_contract_address(),
+            &principal_paid,
+            &interest_paid,
+        );
+
+        let balance = Self::revolving_balance(&line);
+        events::emit_line_repaid(&env, &borrower, amount, interest_paid, balance);
+
+        Self::exit_non_reentrant(&env);
+        balance
+    }
+
+    /// Close every billing cycle of the user's line that has ended, up to
+    /// `MAX_STATEMENTS_PER_CALL` per call. Permissionless, so a keeper can
+    /// bring an idle line's statements up to date.
+    ///
+    /// Returns the number of statements closed.
+    pub fn close_statements(env: Env, user: Address) -> u32 {
+        let mut line = Self::read_open_line(&env, &user);
+        let closed = Self::close_elapsed_cycles(&env, &mut line);
+        if closed > 0 {
+            storage::write_revolving_line(&env, &line);
+        }
+        closed
+    }
+
+    /// Close a fully repaid line. The partial billing cycle gets a final
+    /// statement ending now.
+    pub fn close_revolving_line(env: Env, user: Address) {
+        user.require_auth();
+
+        let mut line = Self::read_open_line(&env, &user);
+        Self::settle_revolving_line(&env, &mut line);
+        if Self::revolving_balance(&line) != 0 {
+            panic_with_error!(&env, CreditLineError::RevolvingLineNotSettled);
+        }
+
+        Self::close_cycle(&env, &mut line, env.ledger().timestamp());
+        line.status = RevolvingLineStatus::Closed;
+        storage::write_revolving_line(&env, &line);
+        events::emit_line_closed(&env, &user);
+    }
+
+    /// Default a delinquent line: one whose minimum payment has been past due
+    /// for longer than `grace_period_seconds`. Permissionless, like
+    /// `mark_defaulted`. The pool writes off the drawn principal it funded,
+    /// the balance leaves the borrower's active debt, the borrower takes the
+    /// default penalty and the line can no longer be used or reopened.
+    ///
+    /// Returns the balance that was owed.
+    pub fn default_revolving_line(env: Env, user: Address) -> i128 {
+        let mut line = Self::read_open_line(&env, &user);
+        Self::settle_revolving_line(&env, &mut line);
+
+        let params = Self::get_protocol_parameters(&env);
+        let grace_ends_at = line
+            .past_due_since
+            .checked_add(params.grace_period_seconds)
+            .unwrap_or_else(|| panic_with_error!(&env, CreditLineError::Overflow));
+        if line.past_due_since == 0 || env.ledger().timestamp() <= grace_ends_at {
+            panic_with_error!(&env, CreditLineError::LoanNotOverdue);
+        }
+
+        let asset = Self::default_asset(&env);
+        let lp_address = Self::asset_pool(&env, &asset);
+
+        Self::enter_non_reentrant(&env);
+
+        let balance = Self::revolving_balance(&line);
+        let principal_lost = line.principal_outstanding;
+        let interest_lost = line.interest_outstanding;
+        line.status = RevolvingLineStatus::Defaulted;
+        storage::decrease_user_active_debt(&env, &user, &asset, balance);
+        storage::write_revolving_line(&env, &line);
+
+        if principal_lost > 0 {
+            let lp_client = LiquidityPoolContractClient::new(&env, &lp_address);
+            lp_client.write_off(&env.current_contract_address(), &principal_lost);
+        }
+        events::emit_line_defaulted(&env, &user, principal_lost, interest_lost);
+
+        if let Some(reputation_contract) = storage::get_reputation_contract(&env) {
+            let penalty = if principal_lost > params.large_loan_threshold {
+                params.large_loan_default_penalty
+            } else {
+                params.default_penalty
+            };
+            let updater = env.current_contract_address();
+            let _ = env.try_invoke_contract::<(), soroban_sdk::Error>(
+                &reputation_contract,
+                &Symbol::new(&env, "decrease_score"),
+                (updater, user.clone(), penalty).into_val(&env),
+            );
+        }
+
+        Self::exit_non_reentrant(&env);
+        balance
+    }
+
+    /// The user's revolving line, with interest accrued up to now. Writes no
+    /// state.
+    pub fn get_revolving_line(env: Env, user: Address) -> RevolvingLine {
+        let mut line = storage::read_revolving_line(&env, &user)
+            .unwrap_or_else(|| panic_with_error!(&env, CreditLineError::RevolvingLineNotFound));
+        if line.status == RevolvingLineStatus::Open {
+            Self::accrue_revolving_interest(&env, &mut line, env.ledger().timestamp());
+        }
+        line
+    }
+
+    pub fn get_revolving_statement(env: Env, user: Address, cycle: u32) -> RevolvingStatement {
+        storage::read_revolving_statement(&env, &user, cycle)
+            .unwrap_or_else(|| panic_with_error!(&env, CreditLineError::StatementNotFound))
+    }
+
+    fn read_open_line(env: &Env, user: &Address) -> RevolvingLine {
+        let line = storage::read_revolving_line(env, user)
+            .unwrap_or_else(|| panic_with_error!(env, CreditLineError::RevolvingLineNotFound));
+        if line.status != RevolvingLineStatus::Open {
+            panic_with_error!(env, CreditLineError::RevolvingLineClosed);
+        }
+        line
+    }
+
+    fn revolving_balance(line: &RevolvingLine) -> i128 {
+        line.principal_outstanding
+            .saturating_add(line.interest_outstanding)
+    }
+
+    /// Bring the line up to the current ledger time: close ended billing cycles
+    /// and accrue interest to now. Fails with `StatementsPending` when more
+    /// cycles ended than one call may close; `close_statements` catches up.
+    fn settle_revolving_line(env: &Env, line: &mut RevolvingLine) {
+        let now = env.ledger().timestamp();
+        Self::close_elapsed_cycles(env, line);
+
+        let cycle_seconds = Self::get_protocol_parameters(env).revolving_cycle_seconds;
+        if line.cycle_start.saturating_add(cycle_seconds) <= now {
+            panic_with_error!(env, CreditLineError::StatementsPending);
+        }
+
+        let interest = Self::accrue_revolving_interest(env, line, now);
+        storage::increase_user_active_debt(
+            env,
+            &line.borrower,
+            &Self::default_asset(env),
+            interest,
+        );
+    }
+
+    fn close_elapsed_cycles(env: &Env, line: &mut RevolvingLine) -> u32 {
+        let now = env.ledger().timestamp();
+        let cycle_seconds = Self::get_protocol_parameters(env).revolving_cycle_seconds;
+        let mut closed = 0;
+        while closed < types::MAX_STATEMENTS_PER_CALL {
+            let period_end = line.cycle_start.saturating_add(cycle_seconds);
+            if period_end > now {
+                break;
+            }
+            Self::close_cycle(env, line, period_end);
+            closed += 1;
+        }
+        closed
+    }
+
+    /// Accrue interest to `period_end`, store the cycle's statement and start
+    /// the next cycle there. A minimum payment from the previous statement
+    /// still unpaid now makes the line past due; it carries into the new
+    /// minimum, which is `revolving_min_payment_bps` of the closing balance but
+    /// at least the cycle's interest.
+    fn close_cycle(env: &Env, line: &mut RevolvingLine, period_end: u64) {
+        let interest = Self::accrue_revolving_interest(env, line, period_end);
+        storage::increase_user_active_debt(
+            env,
+            &line.borrower,
+            &Self::default_asset(env),
+            interest,
+        );
+
+        let params = Self::get_protocol_parameters(env);
+        if line.minimum_due > 0 && line.past_due_since == 0 {
+            line.past_due_since = period_end;
+        }
+
+        let closing_balance = Self::revolving_balance(line);
+        let minimum_payment = Self::calculate_bps_amount(
+            env,
+            closing_balance,
+            params.revolving_min_payment_bps as i128,
+        )
+        .max(line.cycle_interest)
+        .checked_add(line.minimum_due)
+        .unwrap_or_else(|| panic_with_error!(env, CreditLineError::Overflow))
+        .min(closing_balance);
+        line.minimum_due = minimum_payment;
+
+        let statement = RevolvingStatement {
+            cycle: line.cycle,
+            period_start: line.cycle_start,
+            period_end,
+            opening_balance: line.cycle_opening_balance,
+            drawn: line.cycle_drawn,
+            repaid: line.cycle_repaid,
+            interest_charged: line.cycle_interest,
+            closing_balance,
+            minimum_payment,
+            payment_due_at: period_end.saturating_add(params.revolving_cycle_seconds),
+        };
+        storage::write_revolving_statement(env, &line.borrower, &statement);
+        events::emit_line_statement(env, &line.borrower, &statement);
+
+        line.cycle += 1;
+        line.cycle_start = period_end;
+        line.cycle_opening_balance = closing_balance;
+        line.cycle_drawn = 0;
+        line.cycle_repaid = 0;
+        line.cycle_interest = 0;
+    }
+
+    /// Simple interest on the drawn principal at the line's annual rate, from
+    /// the last accrual to `to`. Updates the line in place and returns the
+    /// amount accrued; the caller adds it to the borrower's active debt.
+    fn accrue_revolving_interest(env: &Env, line: &mut RevolvingLine, to: u64) -> i128 {
+        if to <= line.last_accrual_at {
+            return 0;
+        }
+
+        let elapsed = (to - line.last_accrual_at) as i128;
+        let interest = line
+            .principal_outstanding
+            .checked_mul(line.interest_rate_bps as i128)
+            .and_then(|v| v.checked_mul(elapsed))
+            .and_then(|v| v.checked_div(types::BPS_DENOMINATOR * types::SECONDS_PER_YEAR as i128))
+            .unwrap_or_else(|| panic_with_error!(env, CreditLineError::Overflow));
+
+        line.last_accrual_at = to;
+        line.interest_outstanding = line
+            .interest_outstanding
+            .checked_add(interest)
+            .unwrap_or_else(|| panic_with_error!(env, CreditLineError::Overflow));
+        line.cycle_interest = line
+            .cycle_interest
+            .checked_add(interest)
+            .unwrap_or_else(|| panic_with_error!(env, CreditLineError::Overflow));
+        interest
+    }
+
+    /// Collect a payment on a defaulted loan. Anyone may pay; the amount goes to
+    /// the pool as loss recovery rather than interest. The debt still owed is
+    /// the balance unpaid at default less the forfeited guarantee; once it is
+    /// cleared the loan moves to `Recovered` and part of the default penalty is
+    /// restored to the borrower's reputation.
+    ///
+    /// Returns the recovery balance still owed.
+    pub fn repay_defaulted_loan(env: Env, payer: Address, loan_id: u64, amount: i128) -> i128 {
+        payer.require_auth();
+
+        let mut loan = storage::read_loan(&env, loan_id)
+            .unwrap_or_else(|| panic_with_error!(&env, CreditLineError::LoanNotFound));
+
+        if loan.status != LoanStatus::Defaulted {
+            panic_with_error!(&env, CreditLineError::LoanNotDefaulted);
+        }
+
+        let outstanding = Self::recovery_outstanding(&loan);
+        if amount <= 0 || amount > outstanding {
+            panic_with_error!(&env, CreditLineError::InvalidRepaymentAmount);
+        }
+
+        let lp_address = Self::asset_pool(&env, &loan.asset);
+        let token_address = loan.asset.clone();
+
+        Self::enter_non_reentrant(&env);
+
+        loan.recovered_amount = loan
+            .recovered_amount
+            .checked_add(amount)
+            .unwrap_or_else(|| panic_with_error!(&env, CreditLineError::Overflow));
+        let still_owed = outstanding - amount;
+        let is_settled = still_owed == 0;
+        if is_settled {
+            loan.status = LoanStatus::Recovered;
+        }
+        storage::write_loan(&env, &loan);
+
+        let token_client = token::Client::new(&env, &token_address);
+        if let Some(holder) = &loan.beneficiary {
+            token_client.transfer(&payer, holder, &amount);
+        } else {
+            token_client.transfer(&payer, &env.current_contract_address(), &amount);
+            Self::authorize_token_transfer(&env, &token_address, &lp_address, amount);
+            let lp_client = LiquidityPoolContractClient::new(&env, &lp_address);
+            lp_client.receive_recovery(&env.current_contract_address(), &amount);
+        }
+
+        events::emit_recovery_payment(
+            &env,
+            &loan.borrower,
+            loan_id,
+            &payer,
+            amount,
+            still_owed,
+            is_settled,
+        );
+        Self::record_history(
+            &env,
+            loan_id,
+            LoanAction::RecoveryPayment,
+            amount,
+            still_owed,
+        );
+
+        if is_settled {
+            Self::restore_reputation_after_recovery(&env, &loan);
+        }
+
+        Self::exit_non_reentrant(&env);
+        still_owed
+    }
+
+    /// Pool capital still lent out in `loan`. The pool funded the principal
+    /// less the guarantee and is repaid first, so this is the outstanding
+    /// principal beyond the guarantee.
+    fn pool_capital(loan: &Loan) -> i128 {
+        loan.principal_outstanding
+            .saturating_sub(loan.guarantee_amount)
+            .max(0)
+    }
+
+    /// Debt still owed on a defaulted loan: the unpaid balance at default, less
+    /// the forfeited guarantee and any recovery payments.
+    fn recovery_outstanding(loan: &Loan) -> i128 {
+        loan.remaining_balance
+            .saturating_sub(loan.guarantee_amount)
+            .saturating_sub(loan.recovered_amount)
+            .max(0)
+    }
+
+    fn restore_reputation_after_recovery(env: &Env, loan: &Loan) {
+        let Some(reputation_contract) = storage::get_reputation_contract(env) else {
+            return;
+        };
+
+        let restore_bps = Self::get_protocol_parameters(env).recovery_score_restore_bps;
+        let penalty = Self::calculate_default_penalty(env, loan);
+        let restored = (penalty as u64 * restore_bps as u64 / types::BPS_DENOMINATOR as u64) as u32;
+        if restored == 0 {
+            return;
+        }
+
+        let updater = env.current_contract_address();
+        let result = env.try_invoke_contract::<(), soroban_sdk::Error>(
+            &reputation_contract,
+            &Symbol::new(env, "increase_score"),
+            (updater, loan.borrower.clone(), restored).into_val(env),
+        );
+        if result.is_ok() {
+            events::emit_reputation_updated(env, &loan.borrower, restored, true);
+        }
+    }
+
+    /// End of the grace window for an active loan that is past its final due
+    /// date. Fails with `LoanNotActive` or `LoanNotOverdue` otherwise.
+    fn grace_period_end(env: &Env, loan: &Loan) -> Result<u64, CreditLineError> {
+        if loan.status != LoanStatus::Active {
+            return Err(CreditLineError::LoanNotActive);
+        }
+
+        let last_installment = loan
+            .repayment_schedule
+            .last()
+            .ok_or(CreditLineError::Overflow)?;
+
+        if env.ledger().timestamp() <= last_installment.due_date {
+            return Err(CreditLineError::LoanNotOverdue);
+        }
+
+        let params = Self::get_protocol_parameters(env);
+        last_installment
+            .due_date
+            .checked_add(params.grace_period_seconds)
+            .ok_or(CreditLineError::Overflow)
+    }
+
+    fn is_defaultable(env: &Env, loan: &Loan) -> bool {
+        match Self::grace_period_end(env, loan) {
+            Ok(grace_ends_at) => env.ledger().timestamp() > grace_ends_at,
+            Err(_) => false,
+        }
+    }
+
+    /// Move a loan to `Defaulted`, forward the guarantee (less `keeper_bounty`,
+    /// which stays in this contract for the caller to pay out) to the pool, or
+    /// to the receivable holder if the loan was assigned, and apply the
+    /// reputation penalty.
+    fn execute_default(env: &Env, loan: &mut Loan, keeper_bounty: i128) {
+        let lp_address = &Self::asset_pool(env, &loan.asset);
+        let token_address = &loan.asset;
+        loan.status = LoanStatus::Defaulted;
+        storage::remove_restructure_proposal(env, loan.loan_id);
+        storage::decrease_user_active_debt(
+            env,
+            &loan.borrower,
+            &loan.asset,
+            loan.remaining_balance,
+        );
+        storage::write_loan(env, loan);
+
+        let guarantee_to_pool = loan
+            .guarantee_amount
+            .checked_sub(keeper_bounty)
+            .unwrap_or_else(|| panic_with_error!(env, CreditLineError::Underflow));
+        if let Some(holder) = &loan.beneficiary {
+            if guarantee_to_pool > 0 {
+                let token_client = token::Client::new(env, token_address);
+                token_client.transfer(&env.current_contract_address(), holder, &guarantee_to_pool);
+            }
+        } else {
+            let lp_client = LiquidityPoolContractClient::new(env, lp_address);
+
+            // The guarantee first returns pool capital still in the loan; any
+            // excess (most of the principal already repaid) is a gain for the pool.
+            let pool_capital = Self::pool_capital(loan);
+            let capital_returned = guarantee_to_pool.min(pool_capital);
+            let guarantee_surplus = guarantee_to_pool
+                .checked_sub(capital_returned)
+                .unwrap_or_else(|| panic_with_error!(env, CreditLineError::Underflow));
+            if capital_returned > 0 {
+                Self::authorize_token_transfer(env, token_address, lp_address, capital_returned);
+                lp_client.receive_guarantee(&env.current_contract_address(), &capital_returned);
+            }
+            if guarantee_surplus > 0 {
+                Self::authorize_token_transfer(env, token_address, lp_address, guarantee_surplus);
+                lp_client.receive_recovery(&env.current_contract_address(), &guarantee_surplus);
+            }
+
+            // Whatever pool capital the guarantee does not cover is realised as a loss.
+            let principal_lost = pool_capital
+                .checked_sub(capital_returned)
+                .unwrap_or_else(|| panic_with_error!(env, CreditLineError::Underflow));
+            if principal_lost > 0 {
+                lp_client.write_off(&env.current_contract_address(), &principal_lost);
+            }
+        }
+
+        events::emit_loan_defaulted(
+            env,
+            loan.borrower.clone(),
+            loan.loan_id,
+            loan.total_amount,
+            loan.remaining_balance,
+            loan.guarantee_amount,
+        );
+        Self::record_history(
+            env,
+            loan.loan_id,
+            LoanAction::Defaulted,
+            loan.guarantee_amount,
+            Self::recovery_outstanding(loan),
+        );
+
+        if let Some(reputation_contract) = storage::get_reputation_contract(env) {
+            let penalty = Self::calculate_default_penalty(env, loan);
+            let updater = env.current_contract_address();
+            let _ = env.try_invoke_contract::<(), soroban_sdk::Error>(
+                &reputation_contract,
+                &Symbol::new(env, "decrease_score"),
+                (updater, loan.borrower.clone(), penalty).into_val(env),
+            );
+        }
+    }
+
+    pub fn cancel_loan(env: Env, caller: Address, loan_id: u64) {
+        caller.require_auth();
+
+        let loan = storage::read_loan(&env, loan_id)
+            .unwrap_or_else(|| panic_with_error!(&env, CreditLineError::LoanNotFound));
+
+        if loan.status != LoanStatus::Pending {
+            panic_with_error!(&env, CreditLineError::LoanNotCancellable);
+        }
+
+        let admin = storage::get_admin(&env);
+        if caller != loan.borrower && caller != admin {
+            panic_with_error!(&env, CreditLineError::UnauthorizedRepayer);
+        }
+
+        Self::cancel_pending_loan(&env, loan, events::CANCEL_REASON_MANUAL);
+    }
+
+    /// Cancel `Pending` requests older than `pending_loan_ttl_seconds`, scanning
+    /// loan ids `start..start + limit`.  Permissionless so keepers can sweep
+    /// abandoned checkouts; each expired request has its guarantee refunded and
+    /// emits `LOANCNCL` with the `expired` reason.
+    ///
+    /// Returns the number of requests expired in this call.
+    pub fn expire_pending_loans(env: Env, start: u64, limit: u32) -> u32 {
+        let ttl = Self::get_protocol_parameters(&env).pending_loan_ttl_seconds;
+        let loan_count = storage::get_loan_counter(&env);
+        let mut expired = 0u32;
+
+        if ttl == 0 || limit == 0 {
+            return expired;
+        }
+
+        let first = start.max(1);
+        let end = first
+            .saturating_add(limit as u64)
+            .min(loan_count.saturating_add(1));
+        let mut loan_id = first;
+        while loan_id < end {
+            if let Some(loan) = storage::read_loan(&env, loan_id) {
+                if loan.status == LoanStatus::Pending && Self::is_request_expired(&env, &loan, ttl)
+                {
+                    Self::cancel_pending_loan(&env, loan, events::CANCEL_REASON_EXPIRED);
+                    expired += 1;
+                }
+            }
+            loan_id += 1;
+        }
+
+        expired
+    }
+
+    fn is_request_expired(env: &Env, loan: &Loan, ttl: u64) -> bool {
+        ttl > 0 && env.ledger().timestamp() >= loan.created_at.saturating_add(ttl)
+    }
+
+    fn cancel_pending_loan(env: &Env, mut loan: Loan, reason: Symbol) {
+        loan.status = LoanStatus::Cancelled;
+        storage::write_loan(env, &loan);
+
+        let token_client = token::Client::new(env, &loan.asset);
+        token_client.transfer(
+            &env.current_contract_address(),
+            &loan.borrower,
+            &loan.guarantee_amount,
+        );
+
+        events::emit_loan_cancelled(
+            env,
+            &loan.borrower,
+            loan.loan_id,
+            loan.guarantee_amount,
+            reason,
+        );
+        Self::record_history(
+            env,
+            loan.loan_id,
+            LoanAction::Cancelled,
+            loan.guarantee_amount,
+            0,
+        );
+    }
+
+    /// Approve a `Pending` loan request and fund it from the liquidity pool.
+    ///
+    /// May be called by the admin, a risk operator, or the loan's merchant
+    /// confirming the sale.  Merchant, reputation, exposure and liquidity checks
+    /// are re-run against current state because conditions may have changed
+    /// since `request_loan`.  The guarantee is already escrowed, so only the
+    /// pool's contribution moves.
+    pub fn approve_loan(env: Env, approver: Address, loan_id: u64) {
+        approver.require_auth();
+
+        let mut loan = storage::read_loan(&env, loan_id)
+            .unwrap_or_else(|| panic_with_error!(&env, CreditLineError::LoanNotFound));
+
+        if loan.status != LoanStatus::Pending {
+            panic_with_error!(&env, CreditLineError::LoanNotPending);
+        }
+
+        access::require_underwriter(&env, &approver, &loan.merchant);
+
+        let ttl = Self::get_protocol_parameters(&env).pending_loan_ttl_seconds;
+        if Self::is_request_expired(&env, &loan, ttl) {
+            panic_with_error!(&env, CreditLineError::LoanRequestExpired);
+        }
+
+        Self::validate_asset(&env, &loan.asset);
+        Self::validate_merchant(&env, &loan.merchant);
+        let score = Self::validate_reputation(&env, &loan.borrower);
+        Self::validate_exposure(
+            &env,
+            &loan.borrower,
+            &loan.asset,
+            score,
+            loan.remaining_balance,
+        );
+        Self::validate_liquidity(&env, &loan.asset, loan.total_amount, loan.guarantee_amount);
+        Self::enter_non_reentrant(&env);
+
+        loan.status = LoanStatus::Active;
+        loan.funded_at = env.ledger().timestamp();
+        storage::increase_user_active_debt(
+            &env,
+            &loan.borrower,
+            &loan.asset,
+            loan.remaining_balance,
+        );
+        storage::write_loan(&env, &loan);
+
+        let pool_contribution = loan
+            .total_amount
+            .checked_sub(loan.guarantee_amount)
+            .unwrap_or_else(|| panic_with_error!(&env, CreditLineError::Underflow));
+        Self::disburse_from_pool(&env, &loan.asset, &loan.merchant, pool_contribution);
+
+        events::emit_loan_funded(
+            &env,
+            &loan.borrower,
+            loan_id,
+            &approver,
+            &loan.merchant,
+            pool_contribution,
+        );
+        Self::record_history(
+            &env,
+            loan_id,
+            LoanAction::Funded,
+            pool_contribution,
+            loan.remaining_balance,
+        );
+
+        Self::exit_non_reentrant(&env);
+    }
+
+    /// Reject a `Pending` loan request and refund the escrowed guarantee.
+    /// Same callers as `approve_loan`.
+    pub fn reject_loan(env: Env, caller: Address, loan_id: u64) {
+        caller.require_auth();
+
+        let mut loan = storage::read_loan(&env, loan_id)
+            .unwrap_or_else(|| panic_with_error!(&env, CreditLineError::LoanNotFound));
+
+        if loan.status != LoanStatus::Pending {
+            panic_with_error!(&env, CreditLineError::LoanNotPending);
+        }
+
+        access::require_underwriter(&env, &caller, &loan.merchant);
+
+        loan.status = LoanStatus::Rejected;
+        storage::write_loan(&env, &loan);
+
+        let token_client = token::Client::new(&env, &loan.asset);
+        token_client.transfer(
+            &env.current_contract_address(),
+            &loan.borrower,
+            &loan.guarantee_amount,
+        );
+
+        events::emit_loan_rejected(
+            &env,
+            &loan.borrower,
+            loan_id,
+            &caller,
+            loan.guarantee_amount,
+        );
+        Self::record_history(
+            &env,
+            loan_id,
+            LoanAction::Rejected,
+            loan.guarantee_amount,
+            0,
+        );
+    }
+
+    /// Offer the borrower of an active loan a new repayment schedule. Only the
+    /// admin or a risk operator may propose. The schedule must cover the current
+    /// balance (including late fees accrued so far) plus `restructure_fee` and
+    /// `interest_adjustment`; a newer proposal replaces any pending one.
+    pub fn propose_restructure(
+        env: Env,
+        proposer: Address,
+        loan_id: u64,
+        repayment_schedule: Vec<RepaymentInstallment>,
+        restructure_fee: i128,
+        interest_adjustment: i128,
+    ) {
+        proposer.require_auth();
+        access::require_restructurer(&env, &proposer);
+
+        let loan = storage::read_loan(&env, loan_id)
+            .unwrap_or_else(|| panic_with_error!(&env, CreditLineError::LoanNotFound));
+
+        if loan.status != LoanStatus::Active {
+            panic_with_error!(&env, CreditLineError::LoanNotActive);
+        }
+
+        let mut projected = loan.clone();
+        Self::accrue_late_fees_internal(&env, &mut projected);
+        let new_balance =
+            Self::restructured_balance(&env, &projected, restructure_fee, interest_adjustment);
+        Self::validate_schedule(&env, &repayment_schedule, new_balance);
+
+        let proposal = RestructureProposal {
+            proposed_by: proposer.clone(),
+            repayment_schedule: Self::prepare_schedule(&env, &repayment_schedule),
+            restructure_fee,
+            interest_adjustment,
+            proposed_at: env.ledger().timestamp(),
+        };
+        storage::write_restructure_proposal(&env, loan_id, &proposal);
+
+        events::emit_restructure_proposed(
+            &env,
+            &loan.borrower,
+            loan_id,
+            &proposer,
+            restructure_fee,
+            interest_adjustment,
+        );
+    }
+
+    /// Accept the pending restructuring for a loan. Late fees are accrued up to
+    /// now and stay owed; the open schedule is replaced and late-fee accrual
+    /// restarts from the first overdue installment of the new schedule.
+    ///
+    /// Fails with `ScheduleAmountMismatch` if the balance moved since the
+    /// proposal (a repayment or further late fees), in which case a fresh
+    /// proposal is needed.
+    pub fn accept_restructure(env: Env, borrower: Address, loan_id: u64) {
+        borrower.require_auth();
 
-        if loan.status != LoanStatus::Active {
-            return Err(CreditLineError::LoanNotActive);
-        }
+        let mut loan = storage::read_loan(&env, loan_id)
+            .unwrap_or_else(|| panic_with_error!(&env, CreditLineError::LoanNotFound));
 
-        let last_installment = loan
-            .repayment_schedule
-            .last()
-            .ok_or(CreditLineError::Overflow)?;
+        if loan.borrower != borrower {
+            panic_with_error!(&env, CreditLineError::NotBorrower);
+        }
 
-        let now = env.ledger().timestamp();
-        if now <= last_installment.due_date {
-            return Err(CreditLineError::LoanNotOverdue);
+        if loan.status != LoanStatus::Active {
+            panic_with_error!(&env, CreditLineError::LoanNotActive);
         }
 
-        let params = Self::get_protocol_parameters(&env);
-        let grace_ends_at = last_installment
-            .due_date
-            .checked_add(params.grace_period_seconds)
-            .ok_or(CreditLineError::Overflow)?;
+        let proposal = storage::read_restructure_proposal(&env, loan_id)
+            .unwrap_or_else(|| panic_with_error!(&env, CreditLineError::RestructureNotProposed));
 
-        if now <= grace_ends_at {
-            // Still within the grace window — emit a warning and block hard default.
-            events::emit_loan_in_grace_period(
+        let accrued_fee = Self::accrue_late_fees_internal(&env, &mut loan);
+        if accrued_fee > 0 {
+            storage::increase_user_active_debt(&env, &borrower, &loan.asset, accrued_fee);
+            events::emit_late_fee_accrued(
                 &env,
-                &loan.borrower,
+                &borrower,
                 loan_id,
+                accrued_fee,
+                loan.remaining_balance,
+            );
+            Self::record_history(
+                &env,
+                loan_id,
+                LoanAction::LateFeeAccrued,
+                accrued_fee,
                 loan.remaining_balance,
-                grace_ends_at,
             );
-            return Err(CreditLineError::LoanInGracePeriod);
         }
 
-        let lp_address =
-            storage::get_liquidity_pool(&env).ok_or(CreditLineError::InsufficientLiquidity)?;
-
-        Self::enter_non_reentrant(&env);
+        let new_balance = Self::restructured_balance(
+            &env,
+            &loan,
+            proposal.restructure_fee,
+            proposal.interest_adjustment,
+        );
+        Self::validate_schedule(&env, &proposal.repayment_schedule, new_balance);
 
-        loan.status = LoanStatus::Defaulted;
-        storage::decrease_user_active_debt(&env, &loan.borrower, loan.remaining_balance);
-        storage::write_loan(&env, &loan);
+        loan.interest_amount = loan
+            .interest_amount
+            .checked_add(proposal.interest_adjustment)
+            .unwrap_or_else(|| panic_with_error!(&env, CreditLineError::Overflow));
+        loan.interest_outstanding = loan
+            .interest_outstanding
+            .checked_add(proposal.interest_adjustment)
+            .unwrap_or_else(|| panic_with_error!(&env, CreditLineError::Overflow));
+        loan.service_fee_amount = loan
+            .service_fee_amount
+            .checked_add(proposal.restructure_fee)
+            .unwrap_or_else(|| panic_with_error!(&env, CreditLineError::Overflow));
+        loan.service_fee_outstanding = loan
+            .service_fee_outstanding
+            .checked_add(proposal.restructure_fee)
+            .unwrap_or_else(|| panic_with_error!(&env, CreditLineError::Overflow));
 
-        let token_address = storage::get_token(&env).ok_or(CreditLineError::TokenNotConfigured)?;
-        Self::authorize_token_transfer(&env, &token_address, &lp_address, loan.guarantee_amount);
+        let balance_change = new_balance
+            .checked_sub(loan.remaining_balance)
+            .unwrap_or_else(|| panic_with_error!(&env, CreditLineError::Underflow));
+        if balance_change > 0 {
+            storage::increase_user_active_debt(&env, &borrower, &loan.asset, balance_change);
+        } else if balance_change < 0 {
+            storage::decrease_user_active_debt(&env, &borrower, &loan.asset, -balance_change);
+        }
 
-        let lp_client = LiquidityPoolContractClient::new(&env, &lp_address);
-        lp_client.receive_guarantee(&env.current_contract_address(), &loan.guarantee_amount);
+        loan.remaining_balance = new_balance;
+        loan.repayment_schedule = Self::prepare_schedule(&env, &proposal.repayment_schedule);
+        loan.late_fee_accrual_timestamp = 0;
+        loan.restructure_count = loan
+            .restructure_count
+            .checked_add(1)
+            .unwrap_or_else(|| panic_with_error!(&env, CreditLineError::Overflow));
+
+        storage::remove_restructure_proposal(&env, loan_id);
+        storage::write_loan(&env, &loan);
 
-        events::emit_loan_defaulted(
+        events::emit_loan_restructured(
             &env,
-            loan.borrower.clone(),
+            &borrower,
             loan_id,
-            loan.total_amount,
+            loan.restructure_count,
+            loan.remaining_balance,
+            loan.repayment_schedule.len(),
+        );
+        Self::record_history(
+            &env,
+            loan_id,
+            LoanAction::Restructured,
+            proposal
+                .restructure_fee
+                .checked_add(proposal.interest_adjustment)
+                .unwrap_or_else(|| panic_with_error!(&env, CreditLineError::Overflow)),
             loan.remaining_balance,
-            loan.guarantee_amount,
         );
+    }
 
-        if let Some(reputation_contract) = storage::get_reputation_contract(&env) {
-            let penalty = Self::calculate_default_penalty(&env, &loan);
-            let updater = env.current_contract_address();
-            let _ = env.try_invoke_contract::<(), soroban_sdk::Error>(
-                &reputation_contract,
-                &Symbol::new(&env, "decrease_score"),
-                (updater, loan.borrower, penalty).into_val(&env),
-            );
-        }
+    /// Paginated audit trail of a loan, oldest entry first
+    pub fn get_loan_history(
+        env: Env,
+        loan_id: u64,
+        start: u64,
+        limit: u32,
+    ) -> Vec<LoanHistoryEntry> {
+        storage::get_loan_history_paginated(&env, loan_id, start, limit)
+    }
 
-        Self::exit_non_reentrant(&env);
-        Ok(())
+    pub fn get_loan_history_count(env: Env, loan_id: u64) -> u64 {
+        storage::get_loan_history_count(&env, loan_id)
     }
 
-    pub fn cancel_loan(env: Env, caller: Address, loan_id: u64) {
-        caller.require_auth();
+    fn record_history(
+        env: &Env,
+        loan_id: u64,
+        action: LoanAction,
+        amount: i128,
+        balance_after: i128,
+    ) {
+        let entry = LoanHistoryEntry {
+            timestamp: env.ledger().timestamp(),
+            action,
+            amount,
+            balance_after,
+        };
+        storage::append_loan_history(env, loan_id, &entry);
+    }
 
-        let mut loan = storage::read_loan(&env, loan_id)
-            .unwrap_or_else(|| panic_with_error!(&env, CreditLineError::LoanNotFound));
+    pub fn get_restructure_proposal(env: Env, loan_id: u64) -> Option<RestructureProposal> {
+        storage::read_restructure_proposal(&env, loan_id)
+    }
 
-        if loan.status != LoanStatus::Pending {
-            panic_with_error!(&env, CreditLineError::LoanNotCancellable);
+    /// Balance the loan would carry after applying a restructuring fee and
+    /// interest adjustment. The fee cannot be negative and a discount cannot
+    /// exceed the interest still outstanding.
+    fn restructured_balance(
+        env: &Env,
+        loan: &Loan,
+        restructure_fee: i128,
+        interest_adjustment: i128,
+    ) -> i128 {
+        if restructure_fee < 0 {
+            panic_with_error!(env, CreditLineError::InvalidAmount);
         }
 
-        let admin = storage::get_admin(&env);
-        if caller != loan.borrower && caller != admin {
-            panic_with_error!(&env, CreditLineError::UnauthorizedRepayer);
+        let interest_outstanding = loan
+            .interest_outstanding
+            .checked_add(interest_adjustment)
+            .unwrap_or_else(|| panic_with_error!(env, CreditLineError::Overflow));
+        if interest_outstanding < 0 {
+            panic_with_error!(env, CreditLineError::InvalidAmount);
         }
 
-        let token_address = storage::get_token(&env)
-            .unwrap_or_else(|| panic_with_error!(&env, CreditLineError::TokenNotConfigured));
-        let token_client = token::Client::new(&env, &token_address);
-        token_client.transfer(
-            &env.current_contract_address(),
-            &loan.borrower,
-            &loan.guarantee_amount,
-        );
-
-        loan.status = LoanStatus::Cancelled;
-        storage::write_loan(&env, &loan);
-        events::emit_loan_cancelled(&env, &loan.borrower, loan_id, loan.guarantee_amount);
+        loan.remaining_balance
+            .checked_add(restructure_fee)
+            .and_then(|v| v.checked_add(interest_adjustment))
+            .unwrap_or_else(|| panic_with_error!(env, CreditLineError::Overflow))
     }
 
     pub fn repay_loan(env: Env, borrower: Address, loan_id: u64, amount: i128) -> i128 {
         borrower.require_auth();
 
-        let mut loan = storage::read_loan(&env, loan_id)
+        let loan = storage::read_loan(&env, loan_id)
             .unwrap_or_else(|| panic_with_error!(&env, CreditLineError::LoanNotFound));
 
         if loan.borrower != borrower {
             panic_with_error!(&env, CreditLineError::UnauthorizedRepayer);
         }
 
+        Self::process_repayment(&env, &borrower, loan, amount)
+    }
+
+    /// Repay a loan on the borrower's behalf. The payer funds the transfer; the
+    /// borrower still receives the guarantee refund and reputation credit.
+    pub fn repay_loan_for(env: Env, payer: Address, loan_id: u64, amount: i128) -> i128 {
+        payer.require_auth();
+
+        let loan = storage::read_loan(&env, loan_id)
+            .unwrap_or_else(|| panic_with_error!(&env, CreditLineError::LoanNotFound));
+
+        Self::process_repayment(&env, &payer, loan, amount)
+    }
+
+    fn process_repayment(env: &Env, payer: &Address, mut loan: Loan, amount: i128) -> i128 {
+        let borrower = loan.borrower.clone();
+        let loan_id = loan.loan_id;
+
         if loan.status != LoanStatus::Active {
-            panic_with_error!(&env, CreditLineError::LoanNotActive);
+            panic_with_error!(env, CreditLineError::LoanNotActive);
         }
 
         // Accrue any outstanding late fees before validating the payment amount so
         // the borrower repays the true current balance (principal + interest + fees + late fees).
-        let accrued_fee = Self::accrue_late_fees_internal(&env, &mut loan);
+        let accrued_fee = Self::accrue_late_fees_internal(env, &mut loan);
         if accrued_fee > 0 {
-            storage::increase_user_active_debt(&env, &borrower, accrued_fee);
+            storage::increase_user_active_debt(env, &borrower, &loan.asset, accrued_fee);
             events::emit_late_fee_accrued(
-                &env,
+                env,
                 &borrower,
                 loan_id,
                 accrued_fee,
                 loan.remaining_balance,
             );
+            Self::record_history(
+                env,
+                loan_id,
+                LoanAction::LateFeeAccrued,
+                accrued_fee,
+                loan.remaining_balance,
+            );
         }
 
         if amount <= 0 || amount > loan.remaining_balance {
-            panic_with_error!(&env, CreditLineError::InvalidRepaymentAmount);
+            panic_with_error!(env, CreditLineError::InvalidRepaymentAmount);
         }
 
-        Self::enter_non_reentrant(&env);
+        // A payment that covers the discounted payoff closes the loan early; the
+        // rebate is forgiven and the payer is only charged the payoff amount.
+        let rebate = Self::early_payoff_rebate(env, &loan, env.ledger().timestamp());
+        let payoff = loan
+            .remaining_balance
+            .checked_sub(rebate)
+            .unwrap_or_else(|| panic_with_error!(env, CreditLineError::Underflow));
+        let amount = if rebate > 0 && amount >= payoff {
+            loan.interest_outstanding = loan
+                .interest_outstanding
+                .checked_sub(rebate)
+                .unwrap_or_else(|| panic_with_error!(env, CreditLineError::Underflow));
+            loan.remaining_balance = payoff;
+            storage::decrease_user_active_debt(env, &borrower, &loan.asset, rebate);
+            events::emit_early_payoff_rebate(env, &borrower, loan_id, rebate);
+            Self::record_history(
+                env,
+                loan_id,
+                LoanAction::EarlyPayoffRebate,
+                rebate,
+                loan.remaining_balance,
+            );
+            loan.remaining_balance
+        } else {
+            amount
+        };
+
+        Self::enter_non_reentrant(env);
+
+        let pool_capital_before = Self::pool_capital(&loan);
 
         // Payment priority: principal → interest → service fee → late fees
         let principal_paid = amount.min(loan.principal_outstanding);
         let after_principal = amount
             .checked_sub(principal_paid)
-            .unwrap_or_else(|| panic_with_error!(&env, CreditLineError::Underflow));
+            .unwrap_or_else(|| panic_with_error!(env, CreditLineError::Underflow));
         let interest_paid = after_principal.min(loan.interest_outstanding);
         let after_interest = after_principal
             .checked_sub(interest_paid)
-            .unwrap_or_else(|| panic_with_error!(&env, CreditLineError::Underflow));
+            .unwrap_or_else(|| panic_with_error!(env, CreditLineError::Underflow));
         let fee_paid = after_interest.min(loan.service_fee_outstanding);
         let after_fee = after_interest
             .checked_sub(fee_paid)
-            .unwrap_or_else(|| panic_with_error!(&env, CreditLineError::Underflow));
+            .unwrap_or_else(|| panic_with_error!(env, CreditLineError::Underflow));
         let late_fee_paid = after_fee.min(loan.late_fees_outstanding);
 
         loan.principal_outstanding = loan
             .principal_outstanding
             .checked_sub(principal_paid)
-            .unwrap_or_else(|| panic_with_error!(&env, CreditLineError::Underflow));
+            .unwrap_or_else(|| panic_with_error!(env, CreditLineError::Underflow));
         loan.interest_outstanding = loan
             .interest_outstanding
             .checked_sub(interest_paid)
-            .unwrap_or_else(|| panic_with_error!(&env, CreditLineError::Underflow));
+            .unwrap_or_else(|| panic_with_error!(env, CreditLineError::Underflow));
         loan.service_fee_outstanding = loan
             .service_fee_outstanding
             .checked_sub(fee_paid)
-            .unwrap_or_else(|| panic_with_error!(&env, CreditLineError::Underflow));
+            .unwrap_or_else(|| panic_with_error!(env, CreditLineError::Underflow));
         loan.late_fees_outstanding = loan
             .late_fees_outstanding
             .checked_sub(late_fee_paid)
-            .unwrap_or_else(|| panic_with_error!(&env, CreditLineError::Underflow));
+            .unwrap_or_else(|| panic_with_error!(env, CreditLineError::Underflow));
 
         let new_balance = loan
             .remaining_balance
             .checked_sub(amount)
-            .unwrap_or_else(|| panic_with_error!(&env, CreditLineError::Underflow));
+            .unwrap_or_else(|| panic_with_error!(env, CreditLineError::Underflow));
 
         loan.remaining_balance = new_balance;
         let is_fully_repaid = new_balance == 0;
         if is_fully_repaid {
             loan.status = LoanStatus::Repaid;
+            storage::remove_restructure_proposal(env, loan.loan_id);
         }
+        Self::apply_payment_to_installments(env, &mut loan, amount, is_fully_repaid);
 
-        storage::decrease_user_active_debt(&env, &borrower, amount);
-        storage::write_loan(&env, &loan);
-
-        let lp_address = storage::get_liquidity_pool(&env)
-            .unwrap_or_else(|| panic_with_error!(&env, CreditLineError::InsufficientLiquidity));
-        let token_address = storage::get_token(&env)
-            .unwrap_or_else(|| panic_with_error!(&env, CreditLineError::TokenNotConfigured));
+        storage::decrease_user_active_debt(env, &borrower, &loan.asset, amount);
+        storage::write_loan(env, &loan);
 
-        let token_client = token::Client::new(&env, &token_address);
-        token_client.transfer(&borrower, &env.current_contract_address(), &amount);
-        Self::authorize_token_transfer(&env, &token_address, &lp_address, amount);
+        let token_address = loan.asset.clone();
 
-        let lp_client = LiquidityPoolContractClient::new(&env, &lp_address);
-        lp_client.receive_repayment(
-            &env.current_contract_address(),
-            &principal_paid,
-            &interest_paid
-                .checked_add(fee_paid)
-                .and_then(|v| v.checked_add(late_fee_paid))
-                .unwrap_or_else(|| panic_with_error!(&env, CreditLineError::Overflow)),
-        );
+        let token_client = token::Client::new(env, &token_address);
+        if let Some(holder) = &loan.beneficiary {
+            token_client.transfer(payer, holder, &amount);
+        } else {
+            token_client.transfer(payer, &env.current_contract_address(), &amount);
+            let capital_repaid = pool_capital_before
+                .checked_sub(Self::pool_capital(&loan))
+                .unwrap_or_else(|| panic_with_error!(env, CreditLineError::Underflow));
+            Self::pay_pool(
+                env,
+                &token_address,
+                principal_paid,
+                capital_repaid,
+                interest_paid
+                    .checked_add(fee_paid)
+                    .and_then(|v| v.checked_add(late_fee_paid))
+                    .unwrap_or_else(|| panic_with_error!(env, CreditLineError::Overflow)),
+            );
+        }
 
         if is_fully_repaid {
             token_client.transfer(
@@ -651,16 +2693,20 @@ impl CreditLineContract {
         }
 
         events::emit_loan_repaid(
-            &env,
+            env,
             &borrower,
             loan_id,
             amount,
             new_balance,
             is_fully_repaid,
         );
+        Self::record_history(env, loan_id, LoanAction::Repayment, amount, new_balance);
+        if payer != &borrower {
+            events::emit_sponsored_repayment(env, &borrower, loan_id, payer, amount);
+        }
 
         if is_fully_repaid {
-            if let Some(reputation_contract) = storage::get_reputation_contract(&env) {
+            if let Some(reputation_contract) = storage::get_reputation_contract(env) {
                 let updater = env.current_contract_address();
                 let payment_date = env.ledger().timestamp();
                 let due_date = loan
@@ -669,7 +2715,7 @@ impl CreditLineContract {
                     .map(|i| i.due_date)
                     .unwrap_or(0);
                 Self::handle_reputation_increase(
-                    &env,
+                    env,
                     &reputation_contract,
                     &updater,
                     &borrower,
@@ -679,25 +2725,69 @@ impl CreditLineContract {
             }
         }
 
-        Self::exit_non_reentrant(&env);
+        Self::exit_non_reentrant(env);
         new_balance
     }
 
+    /// Forward a payment already held by this contract to the asset's pool.
+    /// The `capital_repaid` part of `principal` pays down pool capital and
+    /// releases locked liquidity; principal beyond it is covered by the
+    /// guarantee, was never funded by the pool and is booked as a gain through
+    /// `receive_recovery`. `interest` is split as usual.
+    fn pay_pool(
+        env: &Env,
+        token_address: &Address,
+        principal: i128,
+        capital_repaid: i128,
+        interest: i128,
+    ) {
+        let lp_address = &Self::asset_pool(env, token_address);
+        let surplus = principal
+            .checked_sub(capital_repaid)
+            .unwrap_or_else(|| panic_with_error!(env, CreditLineError::Underflow));
+        let lp_client = LiquidityPoolContractClient::new(env, lp_address);
+
+        let repayment = capital_repaid
+            .checked_add(interest)
+            .unwrap_or_else(|| panic_with_error!(env, CreditLineError::Overflow));
+        if repayment > 0 {
+            Self::authorize_token_transfer(env, token_address, lp_address, repayment);
+            lp_client.receive_repayment(
+                &env.current_contract_address(),
+                &capital_repaid,
+                &interest,
+            );
+        }
+        if surplus > 0 {
+            Self::authorize_token_transfer(env, token_address, lp_address, surplus);
+            lp_client.receive_recovery(&env.current_contract_address(), &surplus);
+        }
+    }
+
     /// Accrue late fees for a loan and update the caller-supplied `loan` in place.
     ///
-    /// Fees are calculated as `remaining_balance × LATE_FEE_BPS_PER_DAY × days_overdue`
-    /// starting from the earliest overdue installment due date (or the previous accrual
-    /// timestamp, whichever is later). Only complete days are counted; any partial day
-    /// carries over to the next accrual.
+    /// Fees are calculated as `base × late_fee_bps_per_day × days_overdue`, where
+    /// `base` is the remaining balance or the outstanding principal depending on
+    /// `late_fee_base`, starting from the earliest overdue installment due date (or
+    /// the previous accrual timestamp, whichever is later). Only complete days are
+    /// counted; any partial day carries over to the next accrual. The loan's
+    /// daily rate is clamped to `late_fee_apr_cap_bps / 365` and the loan's
+    /// lifetime late fees never exceed `max_late_fee_bps` of its principal.
+    /// Late fees already charged are left out of a `RemainingBalance` base, so
+    /// fees never compound past the annual cap.
     ///
     /// Returns the newly accrued fee amount (0 if nothing was due).
     fn accrue_late_fees_internal(env: &Env, loan: &mut Loan) -> i128 {
-        let now = env.ledger().timestamp();
+        Self::accrue_late_fees_at(env, loan, env.ledger().timestamp())
+    }
 
-        // Find the earliest overdue installment due date.
+    /// Accrue late fees as of `now` rather than the current ledger time. Used
+    /// directly by `get_payoff_quote` to simulate accrual on a copy of the loan.
+    fn accrue_late_fees_at(env: &Env, loan: &mut Loan, now: u64) -> i128 {
+        // Find the earliest overdue installment due date that is still unpaid.
         let mut overdue_since: Option<u64> = None;
         for installment in loan.repayment_schedule.iter() {
-            if installment.due_date < now {
+            if installment.due_date < now && !installment.is_paid() {
                 overdue_since = Some(match overdue_since {
                     None => installment.due_date,
                     Some(d) => {
@@ -736,12 +2826,35 @@ impl CreditLineContract {
             return 0; // less than one full day has passed since last accrual
         }
 
-        let fee = loan
-            .remaining_balance
-            .checked_mul(types::LATE_FEE_BPS_PER_DAY)
+        let params = Self::get_protocol_parameters(env);
+        let base = match params.late_fee_base {
+            LateFeeBase::RemainingBalance => loan
+                .remaining_balance
+                .saturating_sub(loan.late_fees_outstanding)
+                .max(0),
+            LateFeeBase::Principal => loan.principal_outstanding,
+        };
+
+        // Annualised rate, clamped to the APR cap, then spread back over the year
+        // so the cap is applied without losing precision to a per-day division.
+        // Validation keeps governed rates within the cap; the clamp also covers
+        // the built-in defaults and parameters upgraded from the first release.
+        let annual_bps = (params.late_fee_bps_per_day as i128 * DAYS_PER_YEAR as i128)
+            .min(params.late_fee_apr_cap_bps as i128);
+        let uncapped_fee = base
+            .checked_mul(annual_bps)
             .and_then(|v| v.checked_mul(days_elapsed))
+            .and_then(|v| v.checked_div(types::BPS_DENOMINATOR * DAYS_PER_YEAR as i128))
+            .unwrap_or(0);
+
+        let max_late_fees = loan
+            .total_amount
+            .checked_mul(params.max_late_fee_bps as i128)
             .and_then(|v| v.checked_div(types::BPS_DENOMINATOR))
             .unwrap_or(0);
+        let fee = uncapped_fee
+            .min(max_late_fees.saturating_sub(loan.late_fees_accrued))
+            .max(0);
 
         if fee == 0 {
             return 0;
@@ -755,6 +2868,10 @@ impl CreditLineContract {
             .late_fees_outstanding
             .checked_add(fee)
             .unwrap_or(loan.late_fees_outstanding);
+        loan.late_fees_accrued = loan
+            .late_fees_accrued
+            .checked_add(fee)
+            .unwrap_or(loan.late_fees_accrued);
         loan.remaining_balance = loan
             .remaining_balance
             .checked_add(fee)
@@ -763,6 +2880,91 @@ impl CreditLineContract {
         fee
     }
 
+    /// Interest forgiven when a loan is paid off at `now`: `early_payoff_rebate_bps`
+    /// of the interest not yet earned, treating interest as earned linearly from
+    /// funding to the final due date. Zero unless `now` is at least
+    /// `early_payoff_min_lead_seconds` before the final due date, and zero while
+    /// any installment is past due.
+    fn early_payoff_rebate(env: &Env, loan: &Loan, now: u64) -> i128 {
+        let params = Self::get_protocol_parameters(env);
+        if params.early_payoff_rebate_bps == 0 {
+            return 0;
+        }
+
+        let past_due = loan
+            .repayment_schedule
+            .iter()
+            .any(|installment| !installment.is_paid() && installment.due_date < now);
+        if past_due {
+            return 0;
+        }
+
+        let final_due_date = loan
+            .repayment_schedule
+            .last()
+            .map(|i| i.due_date)
+            .unwrap_or(0);
+        if final_due_date <= loan.funded_at || now < loan.funded_at {
+            return 0;
+        }
+
+        let lead_time = final_due_date.saturating_sub(now);
+        if lead_time == 0 || lead_time < params.early_payoff_min_lead_seconds {
+            return 0;
+        }
+
+        let unearned_interest = loan
+            .interest_amount
+            .checked_mul(lead_time as i128)
+            .and_then(|v| v.checked_div((final_due_date - loan.funded_at) as i128))
+            .unwrap_or_else(|| panic_with_error!(env, CreditLineError::Overflow));
+        let rebate = Self::calculate_bps_amount(
+            env,
+            unearned_interest,
+            params.early_payoff_rebate_bps as i128,
+        );
+
+        rebate.clamp(0, loan.interest_outstanding.max(0))
+    }
+
+    /// Quote the amount that would close an active loan at `at_timestamp`
+    /// (which must not be in the past), including late fees that would have
+    /// accrued by then and any early-payoff rebate. Writes no state.
+    pub fn get_payoff_quote(env: Env, loan_id: u64, at_timestamp: u64) -> PayoffQuote {
+        let mut loan = storage::read_loan(&env, loan_id)
+            .unwrap_or_else(|| panic_with_error!(&env, CreditLineError::LoanNotFound));
+
+        if loan.status != LoanStatus::Active {
+            panic_with_error!(&env, CreditLineError::LoanNotActive);
+        }
+
+        if at_timestamp < env.ledger().timestamp() {
+            panic_with_error!(&env, CreditLineError::InvalidQuoteTimestamp);
+        }
+
+        Self::accrue_late_fees_at(&env, &mut loan, at_timestamp);
+        let rebate = Self::early_payoff_rebate(&env, &loan, at_timestamp);
+        let interest = loan
+            .interest_outstanding
+            .checked_sub(rebate)
+            .unwrap_or_else(|| panic_with_error!(&env, CreditLineError::Underflow));
+        let total_payoff = loan
+            .remaining_balance
+            .checked_sub(rebate)
+            .unwrap_or_else(|| panic_with_error!(&env, CreditLineError::Underflow));
+
+        PayoffQuote {
+            loan_id,
+            quoted_at: at_timestamp,
+            principal: loan.principal_outstanding,
+            interest,
+            service_fee: loan.service_fee_outstanding,
+            late_fees: loan.late_fees_outstanding,
+            early_payoff_rebate: rebate,
+            total_payoff,
+        }
+    }
+
     /// Apply late fees to an active loan without requiring a repayment.
     ///
     /// Anyone may call this to trigger fee accrual on an overdue loan.  Emits a
@@ -782,7 +2984,8 @@ impl CreditLineContract {
             return;
         }
 
-        storage::increase_user_active_debt(&env, &loan.borrower, accrued_fee);
+        loan.repayment_schedule = Self::refresh_installments(&env, &loan.repayment_schedule);
+        storage::increase_user_active_debt(&env, &loan.borrower, &loan.asset, accrued_fee);
         storage::write_loan(&env, &loan);
         events::emit_late_fee_accrued(
             &env,
@@ -791,6 +2994,13 @@ impl CreditLineContract {
             accrued_fee,
             loan.remaining_balance,
         );
+        Self::record_history(
+            &env,
+            loan_id,
+            LoanAction::LateFeeAccrued,
+            accrued_fee,
+            loan.remaining_balance,
+        );
     }
 
     fn handle_reputation_increase(
@@ -830,6 +3040,34 @@ impl CreditLineContract {
         }
     }
 
+    fn get_credit_limit_tiers(env: &Env) -> Vec<CreditLimitTier> {
+        match storage::get_parameters_contract(env) {
+            Some(address) => env
+                .try_invoke_contract::<Vec<CreditLimitTier>, soroban_sdk::Error>(
+                    &address,
+                    &Symbol::new(env, "get_credit_limit_tiers"),
+                    ().into_val(env),
+                )
+                .unwrap_or_else(|_| panic_with_error!(env, CreditLineError::ParametersUnavailable))
+                .unwrap_or_else(|_| panic_with_error!(env, CreditLineError::ParametersUnavailable)),
+            None => default_credit_limit_tiers(env),
+        }
+    }
+
+    fn get_pricing_model(env: &Env) -> PricingModel {
+        match storage::get_parameters_contract(env) {
+            Some(address) => env
+                .try_invoke_contract::<PricingModel, soroban_sdk::Error>(
+                    &address,
+                    &Symbol::new(env, "get_pricing_model"),
+                    ().into_val(env),
+                )
+                .unwrap_or_else(|_| panic_with_error!(env, CreditLineError::ParametersUnavailable))
+                .unwrap_or_else(|_| panic_with_error!(env, CreditLineError::ParametersUnavailable)),
+            None => default_pricing_model(env),
+        }
+    }
+
     fn enter_non_reentrant(env: &Env) {
         if storage::is_reentrancy_locked(env) {
             panic_with_error!(env, CreditLineError::ReentrancyDetected);
@@ -1,6 +1,9 @@
-use soroban_sdk::{contracttype, symbol_short, Address, Env, Symbol, Vec};
+use soroban_sdk::{contracttype, symbol_short, Address, Env, Map, Symbol, TryFromVal, Val, Vec};
 
-use crate::types::Loan;
+use crate::types::{
+    AssetConfig, Loan, LoanHistoryEntry, LoanStatus, LoanV0, RestructureProposal, RevolvingLine,
+    RevolvingStatement,
+};
 
 // Storage keys
 pub const ADMIN_KEY: Symbol = symbol_short!("ADMIN");
@@ -21,6 +24,21 @@ enum DataKey {
     UserLoanCount(Address),
     UserLoanAt(Address, u64),
     UserActiveDebt(Address),
+    UserAssetDebt(Address, Address),
+    AssetConfig(Address),
+    AssetList,
+    MerchantLoanCount(Address),
+    MerchantLoanAt(Address, u64),
+    StatusLoanCount(LoanStatus),
+    StatusLoanAt(LoanStatus, u64),
+    StatusIndexLength(LoanStatus),
+    LoanStatusSlot(u64),
+    LoanHistoryCount(u64),
+    LoanHistoryAt(u64, u64),
+    RiskOperator(Address),
+    RestructureProposal(u64),
+    RevolvingLine(Address),
+    RevolvingStatement(Address, u32),
 }
 
 /// Get the admin address from storage
@@ -50,11 +68,24 @@ pub fn increment_loan_counter(env: &Env) -> u64 {
 }
 
 /// Read a loan from storage
+///
+/// Loans written by the first release have no `asset` field; they are decoded
+/// as `LoanV0` and upgraded, and take the current layout on their next write.
+/// The field is checked up front because a failed struct decode traps on chain
+/// rather than returning an error.
 pub fn read_loan(env: &Env, loan_id: u64) -> Option<Loan> {
     let shard = loan_shard(loan_id);
-    env.storage()
+    let raw: Map<Symbol, Val> = env
+        .storage()
         .persistent()
-        .get(&DataKey::Loan(shard, loan_id))
+        .get(&DataKey::Loan(shard, loan_id))?;
+
+    if raw.contains_key(Symbol::new(env, "asset")) {
+        Some(Loan::try_from_val(env, raw.as_val()).expect("Unreadable loan"))
+    } else {
+        let legacy = LoanV0::try_from_val(env, raw.as_val()).expect("Unreadable loan");
+        Some(legacy.upgrade(env, get_token(env).expect("Token not set")))
+    }
 }
 
 /// Write a loan to storage
@@ -67,6 +98,29 @@ pub fn write_loan(env: &Env, loan: &Loan) {
     if is_new {
         append_user_loan_index(env, &loan.borrower, loan.loan_id);
     }
+    if !is_indexed(env, loan.loan_id) {
+        append_merchant_loan_index(env, &loan.merchant, loan.loan_id);
+    }
+    update_status_index(env, loan.loan_id, &loan.status);
+}
+
+/// Add a loan to the merchant and status indexes unless it is already in
+/// them. Only first-release loans that have not been written since can be
+/// missing. Returns whether the loan was added.
+pub fn index_loan(env: &Env, loan: &Loan) -> bool {
+    if is_indexed(env, loan.loan_id) {
+        return false;
+    }
+    append_merchant_loan_index(env, &loan.merchant, loan.loan_id);
+    update_status_index(env, loan.loan_id, &loan.status);
+    true
+}
+
+/// Every loan written since the indexes were introduced has a status slot
+fn is_indexed(env: &Env, loan_id: u64) -> bool {
+    env.storage()
+        .persistent()
+        .has(&DataKey::LoanStatusSlot(loan_id))
 }
 
 pub fn get_user_loan_count(env: &Env, borrower: &Address) -> u64 {
@@ -109,6 +163,86 @@ pub fn get_user_loans_paginated(
     limit: u32,
 ) -> Vec<Loan> {
     let loan_ids = get_user_loan_ids_paginated(env, borrower, start, limit);
+    read_loans(env, &loan_ids)
+}
+
+pub fn get_merchant_loan_count(env: &Env, merchant: &Address) -> u64 {
+    env.storage()
+        .persistent()
+        .get(&DataKey::MerchantLoanCount(merchant.clone()))
+        .unwrap_or(0)
+}
+
+/// Loans financed for a merchant, in creation order
+pub fn get_merchant_loans_paginated(
+    env: &Env,
+    merchant: &Address,
+    start: u64,
+    limit: u32,
+) -> Vec<Loan> {
+    let total = get_merchant_loan_count(env, merchant);
+    let mut loan_ids = Vec::new(env);
+
+    if limit == 0 || start >= total {
+        return Vec::new(env);
+    }
+
+    let end = start.saturating_add(limit as u64).min(total);
+    for idx in start..end {
+        let key = DataKey::MerchantLoanAt(merchant.clone(), idx);
+        if let Some(loan_id) = env.storage().persistent().get::<DataKey, u64>(&key) {
+            loan_ids.push_back(loan_id);
+        }
+    }
+
+    read_loans(env, &loan_ids)
+}
+
+pub fn get_status_loan_count(env: &Env, status: &LoanStatus) -> u64 {
+    env.storage()
+        .persistent()
+        .get(&DataKey::StatusLoanCount(status.clone()))
+        .unwrap_or(0)
+}
+
+/// Length of a status's append-only index, vacated positions included.
+/// Indexes written before positions were kept stable are dense, so their
+/// length is the loan count.
+pub fn get_status_index_length(env: &Env, status: &LoanStatus) -> u64 {
+    env.storage()
+        .persistent()
+        .get(&DataKey::StatusIndexLength(status.clone()))
+        .unwrap_or_else(|| get_status_loan_count(env, status))
+}
+
+/// Loans currently in `status` at index positions `start..start + limit`.
+/// Positions never move: a loan leaving the status vacates its position, so
+/// a page skips it and may hold fewer than `limit` loans.
+pub fn get_status_loans_paginated(
+    env: &Env,
+    status: &LoanStatus,
+    start: u64,
+    limit: u32,
+) -> Vec<Loan> {
+    let total = get_status_index_length(env, status);
+    let mut loan_ids = Vec::new(env);
+
+    if limit == 0 || start >= total {
+        return Vec::new(env);
+    }
+
+    let end = start.saturating_add(limit as u64).min(total);
+    for idx in start..end {
+        let key = DataKey::StatusLoanAt(status.clone(), idx);
+        if let Some(loan_id) = env.storage().persistent().get::<DataKey, u64>(&key) {
+            loan_ids.push_back(loan_id);
+        }
+    }
+
+    read_loans(env, &loan_ids)
+}
+
+fn read_loans(env: &Env, loan_ids: &Vec<u64>) -> Vec<Loan> {
     let mut loans = Vec::new(env);
 
     for loan_id in loan_ids.iter() {
@@ -120,31 +254,113 @@ pub fn get_user_loans_paginated(
     loans
 }
 
-pub fn get_user_active_debt(env: &Env, borrower: &Address) -> i128 {
+pub fn get_loan_history_count(env: &Env, loan_id: u64) -> u64 {
+    env.storage()
+        .persistent()
+        .get(&DataKey::LoanHistoryCount(loan_id))
+        .unwrap_or(0)
+}
+
+/// Append an entry to a loan's history. Entries are never rewritten.
+pub fn append_loan_history(env: &Env, loan_id: u64, entry: &LoanHistoryEntry) {
+    let count = get_loan_history_count(env, loan_id);
+    env.storage()
+        .persistent()
+        .set(&DataKey::LoanHistoryAt(loan_id, count), entry);
+    env.storage()
+        .persistent()
+        .set(&DataKey::LoanHistoryCount(loan_id), &(count + 1));
+}
+
+pub fn get_loan_history_paginated(
+    env: &Env,
+    loan_id: u64,
+    start: u64,
+    limit: u32,
+) -> Vec<LoanHistoryEntry> {
+    let total = get_loan_history_count(env, loan_id);
+    let mut entries = Vec::new(env);
+
+    if limit == 0 || start >= total {
+        return entries;
+    }
+
+    let end = start.saturating_add(limit as u64).min(total);
+    for idx in start..end {
+        let key = DataKey::LoanHistoryAt(loan_id, idx);
+        if let Some(entry) = env
+            .storage()
+            .persistent()
+            .get::<DataKey, LoanHistoryEntry>(&key)
+        {
+            entries.push_back(entry);
+        }
+    }
+
+    entries
+}
+
+/// Active debt is tracked per asset. Debt in the default token keeps the
+/// original `UserActiveDebt` key so single-asset deployments read the same data.
+fn active_debt_key(env: &Env, borrower: &Address, asset: &Address) -> DataKey {
+    if get_token(env).as_ref() == Some(asset) {
+        DataKey::UserActiveDebt(borrower.clone())
+    } else {
+        DataKey::UserAssetDebt(borrower.clone(), asset.clone())
+    }
+}
+
+pub fn get_user_active_debt(env: &Env, borrower: &Address, asset: &Address) -> i128 {
     env.storage()
         .persistent()
-        .get(&DataKey::UserActiveDebt(borrower.clone()))
+        .get(&active_debt_key(env, borrower, asset))
         .unwrap_or(0)
 }
 
-pub fn increase_user_active_debt(env: &Env, borrower: &Address, amount: i128) {
-    let current = get_user_active_debt(env, borrower);
+pub fn increase_user_active_debt(env: &Env, borrower: &Address, asset: &Address, amount: i128) {
+    let current = get_user_active_debt(env, borrower, asset);
     let next = current
         .checked_add(amount)
         .expect("User active debt overflow");
     env.storage()
         .persistent()
-        .set(&DataKey::UserActiveDebt(borrower.clone()), &next);
+        .set(&active_debt_key(env, borrower, asset), &next);
 }
 
-pub fn decrease_user_active_debt(env: &Env, borrower: &Address, amount: i128) {
-    let current = get_user_active_debt(env, borrower);
+pub fn decrease_user_active_debt(env: &Env, borrower: &Address, asset: &Address, amount: i128) {
+    let current = get_user_active_debt(env, borrower, asset);
     let next = current
         .checked_sub(amount)
         .expect("User active debt underflow");
     env.storage()
         .persistent()
-        .set(&DataKey::UserActiveDebt(borrower.clone()), &next);
+        .set(&active_debt_key(env, borrower, asset), &next);
+}
+
+/// Whitelisting entry for an asset other than the default token
+pub fn read_asset_config(env: &Env, asset: &Address) -> Option<AssetConfig> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::AssetConfig(asset.clone()))
+}
+
+/// Store an asset's configuration, adding it to the asset list the first time
+pub fn write_asset_config(env: &Env, asset: &Address, config: &AssetConfig) {
+    let key = DataKey::AssetConfig(asset.clone());
+    if !env.storage().persistent().has(&key) {
+        let mut assets = get_assets(env);
+        assets.push_back(asset.clone());
+        env.storage().persistent().set(&DataKey::AssetList, &assets);
+    }
+    env.storage().persistent().set(&key, config);
+}
+
+/// Every asset ever whitelisted, including disabled ones
+pub fn get_assets(env: &Env) -> Vec<Address> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::AssetList)
+        .unwrap_or_else(|| Vec::new(env))
 }
 
 fn append_user_loan_index(env: &Env, borrower: &Address, loan_id: u64) {
@@ -157,10 +373,129 @@ fn append_user_loan_index(env: &Env, borrower: &Address, loan_id: u64) {
         .set(&DataKey::UserLoanCount(borrower.clone()), &(count + 1));
 }
 
+fn append_merchant_loan_index(env: &Env, merchant: &Address, loan_id: u64) {
+    let count = get_merchant_loan_count(env, merchant);
+    env.storage()
+        .persistent()
+        .set(&DataKey::MerchantLoanAt(merchant.clone(), count), &loan_id);
+    env.storage()
+        .persistent()
+        .set(&DataKey::MerchantLoanCount(merchant.clone()), &(count + 1));
+}
+
+/// Move a loan into the index of its current status. Each loan remembers its
+/// (status, position) slot so it can leave the old index in constant time.
+/// Indexes are append-only: leaving one vacates the position rather than
+/// moving another loan into it, so paging through an index while loans
+/// change status never skips or repeats a loan that stays put.
+fn update_status_index(env: &Env, loan_id: u64, status: &LoanStatus) {
+    let slot_key = DataKey::LoanStatusSlot(loan_id);
+    let slot: Option<(LoanStatus, u64)> = env.storage().persistent().get(&slot_key);
+
+    if let Some((current, position)) = slot {
+        if current == *status {
+            return;
+        }
+        remove_from_status_index(env, &current, position);
+    }
+
+    let position = get_status_index_length(env, status);
+    env.storage()
+        .persistent()
+        .set(&DataKey::StatusLoanAt(status.clone(), position), &loan_id);
+    env.storage()
+        .persistent()
+        .set(&DataKey::StatusIndexLength(status.clone()), &(position + 1));
+    env.storage().persistent().set(
+        &DataKey::StatusLoanCount(status.clone()),
+        &(get_status_loan_count(env, status) + 1),
+    );
+    env.storage()
+        .persistent()
+        .set(&slot_key, &(status.clone(), position));
+}
+
+fn remove_from_status_index(env: &Env, status: &LoanStatus, position: u64) {
+    env.storage()
+        .persistent()
+        .remove(&DataKey::StatusLoanAt(status.clone(), position));
+    env.storage().persistent().set(
+        &DataKey::StatusLoanCount(status.clone()),
+        &(get_status_loan_count(env, status) - 1),
+    );
+}
+
 fn loan_shard(loan_id: u64) -> u32 {
     (loan_id % (LOAN_SHARD_COUNT as u64)) as u32
 }
 
+/// Check whether an address may approve or reject pending loans
+pub fn is_risk_operator(env: &Env, operator: &Address) -> bool {
+    env.storage()
+        .persistent()
+        .get(&DataKey::RiskOperator(operator.clone()))
+        .unwrap_or(false)
+}
+
+/// Grant or revoke the risk operator role
+pub fn set_risk_operator(env: &Env, operator: &Address, allowed: bool) {
+    let key = DataKey::RiskOperator(operator.clone());
+    if allowed {
+        env.storage().persistent().set(&key, &true);
+    } else {
+        env.storage().persistent().remove(&key);
+    }
+}
+
+/// Get the pending restructuring proposal for a loan, if any
+pub fn read_restructure_proposal(env: &Env, loan_id: u64) -> Option<RestructureProposal> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::RestructureProposal(loan_id))
+}
+
+/// Store (or replace) the pending restructuring proposal for a loan
+pub fn write_restructure_proposal(env: &Env, loan_id: u64, proposal: &RestructureProposal) {
+    env.storage()
+        .persistent()
+        .set(&DataKey::RestructureProposal(loan_id), proposal);
+}
+
+pub fn remove_restructure_proposal(env: &Env, loan_id: u64) {
+    env.storage()
+        .persistent()
+        .remove(&DataKey::RestructureProposal(loan_id));
+}
+
+pub fn read_revolving_line(env: &Env, borrower: &Address) -> Option<RevolvingLine> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::RevolvingLine(borrower.clone()))
+}
+
+pub fn write_revolving_line(env: &Env, line: &RevolvingLine) {
+    env.storage()
+        .persistent()
+        .set(&DataKey::RevolvingLine(line.borrower.clone()), line);
+}
+
+pub fn read_revolving_statement(
+    env: &Env,
+    borrower: &Address,
+    cycle: u32,
+) -> Option<RevolvingStatement> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::RevolvingStatement(borrower.clone(), cycle))
+}
+
+pub fn write_revolving_statement(env: &Env, borrower: &Address, statement: &RevolvingStatement) {
+    env.storage().persistent().set(
+        &DataKey::RevolvingStatement(borrower.clone(), statement.cycle),
+        statement,
+    );
+}
+
 /// Get the Reputation Contract address
 pub fn get_reputation_contract(env: &Env) -> Option<Address> {
     env.storage().instance().get(&REPUTATION_CONTRACT)
@@ -1,5 +1,5 @@
-use parameters_contract::ProtocolParameters;
-use soroban_sdk::{contracttype, Address};
+use parameters_contract::{CreditLimitTier, PricingModel, ProtocolParameters};
+use soroban_sdk::{contracttype, Address, Env};
 
 // Loan status enum
 #[contracttype]
@@ -10,14 +10,81 @@ pub enum LoanStatus {
     Repaid,
     Defaulted,
     Cancelled,
+    Rejected,
+    Recovered, // defaulted, then repaid in full through recovery payments
+    Refunded,  // purchase refunded by the merchant before being repaid
+}
+
+// Installment status enum
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum InstallmentStatus {
+    Upcoming, // not yet due and not the next installment to pay
+    Due,      // oldest open installment that is not yet overdue
+    Paid,     // fully paid
+    Late,     // past its due date and not fully paid
+}
+
+// Kind of change recorded in a loan's history; `amount` means, per kind:
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum LoanAction {
+    Created,               // total financed (funded immediately)
+    Requested,             // total requested (pending approval)
+    Funded,                // pool contribution paid to the merchant on approval
+    Rejected,              // guarantee refunded
+    Cancelled,             // guarantee refunded (cancelled or expired request)
+    Repayment,             // payment applied
+    EarlyPayoffRebate,     // interest forgiven
+    LateFeeAccrued,        // late fee added to the balance
+    GraceWarning,          // always 0
+    Restructured,          // balance change (restructure fee + interest adjustment)
+    Defaulted,             // guarantee forfeited
+    RecoveryPayment,       // payment toward the defaulted debt
+    MerchantRefund,        // amount refunded by the merchant
+    ReceivableTransferred, // pool capital outstanding in the loan at transfer
+}
+
+// Append-only loan history entry
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LoanHistoryEntry {
+    pub timestamp: u64,
+    pub action: LoanAction,
+    pub amount: i128,
+    pub balance_after: i128, // what the borrower still owes after the action
 }
 
 // Repayment installment structure
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct RepaymentInstallment {
-    pub due_date: u64, // Unix timestamp
-    pub amount: i128,  // Amount due for this installment
+    pub due_date: u64,             // Unix timestamp
+    pub amount: i128,              // Amount due for this installment
+    pub amount_paid: i128,         // Amount applied to this installment so far
+    pub paid_at: u64,              // Timestamp the installment was fully paid (0 = open)
+    pub status: InstallmentStatus, // Refreshed whenever the loan is touched
+}
+
+impl RepaymentInstallment {
+    /// Build an unpaid installment; accounting fields start at zero.
+    pub fn new(due_date: u64, amount: i128) -> Self {
+        RepaymentInstallment {
+            due_date,
+            amount,
+            amount_paid: 0,
+            paid_at: 0,
+            status: InstallmentStatus::Upcoming,
+        }
+    }
+
+    pub fn outstanding(&self) -> i128 {
+        self.amount.saturating_sub(self.amount_paid)
+    }
+
+    pub fn is_paid(&self) -> bool {
+        self.status == InstallmentStatus::Paid
+    }
 }
 
 // Loan data structure
@@ -27,6 +94,8 @@ pub struct Loan {
     pub loan_id: u64,
     pub borrower: Address,
     pub merchant: Address,
+    pub asset: Address,               // token the loan is denominated in
+    pub beneficiary: Option<Address>, // holder of the receivable; None = the asset's pool
     pub total_amount: i128,
     pub guarantee_amount: i128,
     pub interest_rate_bps: u32,
@@ -42,19 +111,223 @@ pub struct Loan {
     pub funded_at: u64,                  // 0 means not funded yet
     pub late_fees_outstanding: i128,     // accumulated unpaid late fees
     pub late_fee_accrual_timestamp: u64, // last accrual timestamp (0 = never accrued)
+    pub late_fees_accrued: i128,         // lifetime late fees, bounded by max_late_fee_bps
+    pub restructure_count: u32,          // number of accepted restructurings
+    pub recovered_amount: i128,          // collected after default (loss recovery)
+    pub refunded_amount: i128,           // paid back by the merchant (merchant_refund)
+}
+
+// Installment as stored by the first release, before per-installment accounting
+#[contracttype(export = false)]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RepaymentInstallmentV0 {
+    pub due_date: u64,
+    pub amount: i128,
+}
+
+// Loan as stored by the first release. Never written: `storage::read_loan`
+// upgrades it to `Loan` when it finds one.
+#[contracttype(export = false)]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LoanV0 {
+    pub loan_id: u64,
+    pub borrower: Address,
+    pub merchant: Address,
+    pub total_amount: i128,
+    pub guarantee_amount: i128,
+    pub interest_rate_bps: u32,
+    pub interest_amount: i128,
+    pub service_fee_amount: i128,
+    pub principal_outstanding: i128,
+    pub interest_outstanding: i128,
+    pub service_fee_outstanding: i128,
+    pub remaining_balance: i128,
+    pub repayment_schedule: soroban_sdk::Vec<RepaymentInstallmentV0>,
+    pub status: LoanStatus,
+    pub created_at: u64,
+    pub funded_at: u64,
+    pub late_fees_outstanding: i128,
+    pub late_fee_accrual_timestamp: u64,
+}
+
+impl LoanV0 {
+    /// The loan in the current layout. First-release loans were all in the
+    /// default token and paid to the pool. What was repaid of the schedule
+    /// (everything due except the open balance net of late fees) is credited
+    /// to installments oldest first; the lifetime late-fee tally starts from
+    /// the fees still outstanding, the only ones on record.
+    pub fn upgrade(self, env: &Env, asset: Address) -> Loan {
+        let open_scheduled = self
+            .remaining_balance
+            .saturating_sub(self.late_fees_outstanding);
+        let mut repaid = self
+            .repayment_schedule
+            .iter()
+            .fold(0i128, |total, installment| {
+                total.saturating_add(installment.amount)
+            })
+            .saturating_sub(open_scheduled)
+            .max(0);
+
+        let mut schedule = soroban_sdk::Vec::new(env);
+        for legacy in self.repayment_schedule.iter() {
+            let mut installment = RepaymentInstallment::new(legacy.due_date, legacy.amount);
+            installment.amount_paid = repaid.min(legacy.amount).max(0);
+            repaid = repaid.saturating_sub(installment.amount_paid);
+            if installment.outstanding() <= 0 {
+                installment.status = InstallmentStatus::Paid;
+            }
+            schedule.push_back(installment);
+        }
+
+        Loan {
+            loan_id: self.loan_id,
+            borrower: self.borrower,
+            merchant: self.merchant,
+            asset,
+            beneficiary: None,
+            total_amount: self.total_amount,
+            guarantee_amount: self.guarantee_amount,
+            interest_rate_bps: self.interest_rate_bps,
+            interest_amount: self.interest_amount,
+            service_fee_amount: self.service_fee_amount,
+            principal_outstanding: self.principal_outstanding,
+            interest_outstanding: self.interest_outstanding,
+            service_fee_outstanding: self.service_fee_outstanding,
+            remaining_balance: self.remaining_balance,
+            repayment_schedule: schedule,
+            status: self.status,
+            created_at: self.created_at,
+            funded_at: self.funded_at,
+            late_fees_outstanding: self.late_fees_outstanding,
+            late_fee_accrual_timestamp: self.late_fee_accrual_timestamp,
+            late_fees_accrued: self.late_fees_outstanding,
+            restructure_count: 0,
+            recovered_amount: 0,
+            refunded_amount: 0,
+        }
+    }
+}
+
+// Pending restructuring offer for an active loan, awaiting borrower acceptance
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RestructureProposal {
+    pub proposed_by: Address,
+    pub repayment_schedule: soroban_sdk::Vec<RepaymentInstallment>, // replaces the open schedule
+    pub restructure_fee: i128,     // added to the service fee outstanding
+    pub interest_adjustment: i128, // added to interest outstanding (negative = discount)
+    pub proposed_at: u64,
+}
+
+// Whitelisted loan asset other than the default token, and the pool that funds it
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AssetConfig {
+    pub pool: Address,   // liquidity pool denominated in this asset
+    pub enabled: bool,   // false: no new loans, existing loans still settle
+    pub has_loans: bool, // set by the first loan in this asset; the pool is then fixed
+    pub decimals: u32,   // token decimals, to express debt in default-token units
+}
+
+// Pricing and schedule a borrower would receive for a given purchase
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LoanQuote {
+    pub total_amount: i128,
+    pub interest_rate_bps: u32,
+    pub interest_amount: i128,
+    pub service_fee_amount: i128,
+    pub total_due: i128, // principal + interest + service fee
+    pub repayment_schedule: soroban_sdk::Vec<RepaymentInstallment>,
+}
+
+// Breakdown of the amount that would close a loan at a given time
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PayoffQuote {
+    pub loan_id: u64,
+    pub quoted_at: u64,
+    pub principal: i128,
+    pub interest: i128, // net of the early-payoff rebate
+    pub service_fee: i128,
+    pub late_fees: i128, // including fees that would accrue by `quoted_at`
+    pub early_payoff_rebate: i128,
+    pub total_payoff: i128,
+}
+
+// Status of a borrower's revolving credit line
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum RevolvingLineStatus {
+    Open,
+    Closed,
+    Defaulted, // a minimum payment stayed unpaid past the grace period
+}
+
+// Revolving credit line: the borrower draws against the limit and repays
+// freely; interest accrues on the drawn principal and is billed per cycle
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RevolvingLine {
+    pub borrower: Address,
+    pub credit_limit: i128,     // refreshed from reputation on every draw
+    pub interest_rate_bps: u32, // annual rate, fixed when the line opens
+    pub principal_outstanding: i128,
+    pub interest_outstanding: i128,
+    pub status: RevolvingLineStatus,
+    pub opened_at: u64,
+    pub last_accrual_at: u64,
+    pub cycle: u32, // number of the open billing cycle, from 1
+    pub cycle_start: u64,
+    pub cycle_opening_balance: i128,
+    pub cycle_drawn: i128,
+    pub cycle_repaid: i128,
+    pub cycle_interest: i128,
+    pub minimum_due: i128,   // minimum payment still owed on the last statement
+    pub past_due_since: u64, // end of the cycle a minimum payment was missed in (0 = current)
+}
+
+// Closed billing cycle of a revolving credit line
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RevolvingStatement {
+    pub cycle: u32,
+    pub period_start: u64,
+    pub period_end: u64,
+    pub opening_balance: i128,
+    pub drawn: i128,
+    pub repaid: i128,
+    pub interest_charged: i128,
+    pub closing_balance: i128, // principal + interest owed at period end
+    pub minimum_payment: i128, // due by `payment_due_at`, including any unpaid earlier minimum
+    pub payment_due_at: u64,   // end of the following cycle
 }
 
 pub fn default_protocol_parameters() -> ProtocolParameters {
     parameters_contract::default_parameters()
 }
 
+pub fn default_credit_limit_tiers(env: &Env) -> soroban_sdk::Vec<CreditLimitTier> {
+    parameters_contract::default_credit_limit_tiers(env)
+}
+
+pub fn default_pricing_model(env: &Env) -> PricingModel {
+    parameters_contract::default_pricing_model(env)
+}
+
 // Constants
-pub const MIN_GUARANTEE_PERCENT: i128 = 20; // 20% minimum guarantee
-pub const MIN_REPUTATION_THRESHOLD: u32 = 50; // Minimum reputation score required
 pub const SERVICE_FEE_BPS: i128 = 100; // 1% flat service fee
 pub const BPS_DENOMINATOR: i128 = 10_000;
-pub const LATE_FEE_BPS_PER_DAY: i128 = 50; // 0.5% of remaining balance per overdue day
 pub const SECONDS_PER_DAY: u64 = 86_400;
+pub const SECONDS_PER_YEAR: u64 = 31_536_000;
+/// Billing cycles a single call may close, bounding storage writes per call.
+pub const MAX_STATEMENTS_PER_CALL: u32 = 12;
+/// Loan ids `mark_defaulted_batch` accepts per call, so a batch cannot run out
+/// of resources partway through.
+pub const MAX_DEFAULT_BATCH: u32 = 25;
+/// Loan ids `index_loans` walks per call.
+pub const MAX_INDEX_BATCH: u32 = 50;
 
 // SC-11: Reputation score increments awarded on full loan repayment
 pub const REPUTATION_INCREMENT_ON_TIME: u32 = 10; // +10 for on-time or late full repayment
@@ -16,4 +16,15 @@ pub enum LiquidityPoolError {
     ZeroTotalShares = 10,
     ReentrancyDetected = 11,
     ContractPaused = 12,
+    WithdrawalQueuePending = 13,
+    WithdrawalRequestNotFound = 14,
+    InsufficientAllowance = 15,
+    InvalidExpirationLedger = 16,
+    SlippageExceeded = 17,
+    FirstDepositTooSmall = 18,
+    InvalidFeeConfig = 19,
+    InsufficientReserve = 20,
+    WriteOffExceedsLocked = 21,
+    NothingToClaim = 22,
+    BurnNotSupported = 23,
 }
@@ -1,11 +1,24 @@
 use soroban_sdk::{symbol_short, Address, Env, Symbol};
 
+use crate::types::FeeConfig;
+
 const DEPOSITED: Symbol = symbol_short!("LQDEPST");
 const WITHDRAWN: Symbol = symbol_short!("LQWTHDR");
 const LOAN_FUNDED: Symbol = symbol_short!("LQFUND");
 const REPAYMENT_RCV: Symbol = symbol_short!("LQREPAY");
 const GUARANTEE_RCV: Symbol = symbol_short!("LQGUART");
+const RECOVERY_RCV: Symbol = symbol_short!("LQRECOV");
+const LOSS_WRITTEN_OFF: Symbol = symbol_short!("LQLOSS");
 const INTEREST_DIST: Symbol = symbol_short!("LQINTDST");
+const RECEIVABLE_SOLD: Symbol = symbol_short!("LQRCVSLD");
+const WITHDRAWAL_QUEUED: Symbol = symbol_short!("LQWQUEUE");
+const WITHDRAWAL_FILLED: Symbol = symbol_short!("LQWQFILL");
+const WITHDRAWAL_CLAIMED: Symbol = symbol_short!("LQWQCLM");
+const FEE_CONFIG_SET: Symbol = symbol_short!("LQFEECFG");
+const FEE_PAID: Symbol = symbol_short!("LQFEEPD");
+const RESERVE_FUNDED: Symbol = symbol_short!("LQRSVADD");
+const RESERVE_WITHDRAWN: Symbol = symbol_short!("LQRSVOUT");
+const RESERVE_ABSORBED: Symbol = symbol_short!("LQRSVABS");
 
 /// Emitted when a liquidity provider deposits tokens
 pub fn emit_liquidity_deposited(env: &Env, provider: &Address, amount: i128, shares_issued: i128) {
@@ -24,6 +37,32 @@ pub fn emit_liquidity_withdrawn(
         .publish((WITHDRAWN, provider), (shares_burned, amount_returned));
 }
 
+/// Emitted when a liquidity provider queues a withdrawal
+pub fn emit_withdrawal_queued(env: &Env, provider: &Address, request_id: u64, shares: i128) {
+    env.events()
+        .publish((WITHDRAWAL_QUEUED, provider, request_id), shares);
+}
+
+/// Emitted when a queued withdrawal is filled, in full or in part
+pub fn emit_withdrawal_filled(
+    env: &Env,
+    provider: &Address,
+    request_id: u64,
+    shares_burned: i128,
+    amount_returned: i128,
+    shares_remaining: i128,
+) {
+    env.events().publish(
+        (WITHDRAWAL_FILLED, provider, request_id),
+        (shares_burned, amount_returned, shares_remaining),
+    );
+}
+
+/// Emitted when a provider claims the tokens of filled withdrawals
+pub fn emit_withdrawal_claimed(env: &Env, provider: &Address, amount: i128) {
+    env.events().publish((WITHDRAWAL_CLAIMED, provider), amount);
+}
+
 /// Emitted when the pool funds a loan (CreditLine → merchant)
 pub fn emit_loan_funded(env: &Env, creditline: &Address, amount: i128) {
     env.events().publish((LOAN_FUNDED, creditline), amount);
@@ -40,6 +79,31 @@ pub fn emit_guarantee_received(env: &Env, creditline: &Address, amount: i128) {
     env.events().publish((GUARANTEE_RCV, creditline), amount);
 }
 
+/// Emitted when a post-default recovery payment is received
+pub fn emit_recovery_received(env: &Env, creditline: &Address, amount: i128) {
+    env.events().publish((RECOVERY_RCV, creditline), amount);
+}
+
+/// Emitted when unrecoverable principal is written off on loan default
+pub fn emit_loss_written_off(env: &Env, creditline: &Address, amount: i128, total_losses: i128) {
+    env.events()
+        .publish((LOSS_WRITTEN_OFF, creditline), (amount, total_losses));
+}
+
+/// Emitted when the pool sells a loan receivable
+pub fn emit_receivable_sold(
+    env: &Env,
+    loan_id: u64,
+    buyer: &Address,
+    capital_released: i128,
+    price: i128,
+) {
+    env.events().publish(
+        (RECEIVABLE_SOLD, loan_id),
+        (buyer.clone(), capital_released, price),
+    );
+}
+
 /// Emitted when interest is distributed to LPs, treasury, and merchant fund
 pub fn emit_interest_distributed(
     env: &Env,
@@ -53,3 +117,78 @@ pub fn emit_interest_distributed(
         (total_interest, lp_amount, protocol_amount, merchant_amount),
     );
 }
+
+/// Emitted when the admin changes the interest fee split
+pub fn emit_fee_config_updated(env: &Env, config: &FeeConfig) {
+    env.events().publish((FEE_CONFIG_SET,), config.clone());
+}
+
+/// Emitted when an extra fee recipient is paid its share of interest
+pub fn emit_fee_paid(env: &Env, recipient: &Address, amount: i128) {
+    env.events().publish((FEE_PAID, recipient), amount);
+}
+
+/// Emitted when the insurance reserve grows, from interest or an admin top-up
+pub fn emit_reserve_funded(env: &Env, amount: i128, reserve_balance: i128) {
+    env.events()
+        .publish((RESERVE_FUNDED,), (amount, reserve_balance));
+}
+
+/// Emitted when the admin withdraws from the insurance reserve
+pub fn emit_reserve_withdrawn(env: &Env, to: &Address, amount: i128, reserve_balance: i128) {
+    env.events()
+        .publish((RESERVE_WITHDRAWN, to), (amount, reserve_balance));
+}
+
+/// Emitted when the insurance reserve absorbs a default loss ahead of LPs
+pub fn emit_reserve_absorbed_loss(
+    env: &Env,
+    creditline: &Address,
+    absorbed: i128,
+    reserve_balance: i128,
+) {
+    env.events()
+        .publish((RESERVE_ABSORBED, creditline), (absorbed, reserve_balance));
+}
+
+// SEP-41 token events for LP shares, in the standard token event layout
+
+/// Emitted when shares move between holders
+pub fn emit_share_transfer(env: &Env, from: &Address, to: &Address, amount: i128) {
+    env.events().publish(
+        (symbol_short!("transfer"), from.clone(), to.clone()),
+        amount,
+    );
+}
+
+/// Emitted when a holder sets a spender's allowance
+pub fn emit_share_approve(
+    env: &Env,
+    from: &Address,
+    spender: &Address,
+    amount: i128,
+    expiration_ledger: u32,
+) {
+    env.events().publish(
+        (symbol_short!("approve"), from.clone(), spender.clone()),
+        (amount, expiration_ledger),
+    );
+}
+
+/// Emitted when a deposit issues new shares; the pool is the minting admin
+pub fn emit_share_mint(env: &Env, to: &Address, amount: i128) {
+    env.events().publish(
+        (
+            symbol_short!("mint"),
+            env.current_contract_address(),
+            to.clone(),
+        ),
+        amount,
+    );
+}
+
+/// Emitted when shares are destroyed (redeemed or burned)
+pub fn emit_share_burn(env: &Env, from: &Address, amount: i128) {
+    env.events()
+        .publish((symbol_short!("burn"), from.clone()), amount);
+}
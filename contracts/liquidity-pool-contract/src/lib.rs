@@ -1,5 +1,8 @@
 #![no_std]
-use soroban_sdk::{contract, contractimpl, panic_with_error, token, Address, Env};
+use soroban_sdk::{
+    contract, contractimpl, panic_with_error, token, token::TokenInterface, Address, Env, IntoVal,
+    String, Symbol,
+};
 
 mod access;
 mod errors;
@@ -8,7 +11,7 @@ mod storage;
 mod types;
 
 pub use errors::LiquidityPoolError;
-pub use types::PoolStats;
+pub use types::{FeeConfig, FeeRecipient, PoolStats, WithdrawalRequest};
 
 #[contract]
 pub struct LiquidityPoolContract;
@@ -23,8 +26,8 @@ impl LiquidityPoolContract {
     ///
     /// * `admin`        – Contract administrator (can update addresses)
     /// * `token`        – SEP-41 token used by the pool (e.g. USDC)
-    /// * `treasury`     – Address that receives the 10% protocol fee
-    /// * `merchant_fund`– Address that receives the 5% merchant incentive fee
+    /// * `treasury`     – Address that receives the protocol fee (10% by default)
+    /// * `merchant_fund`– Address that receives the merchant incentive fee (5% by default)
     pub fn initialize(
         env: Env,
         admin: Address,
@@ -41,6 +44,7 @@ impl LiquidityPoolContract {
         storage::set_token(&env, &token);
         storage::set_treasury(&env, &treasury);
         storage::set_merchant_fund(&env, &merchant_fund);
+        storage::set_share_decimals_offset(&env, types::SHARE_DECIMALS_OFFSET);
     }
 
     // -------------------------------------------------------------------------
@@ -71,6 +75,88 @@ impl LiquidityPoolContract {
         storage::set_admin(&env, &new_admin);
     }
 
+    /// Replace the interest fee split. LP, protocol, merchant, reserve and
+    /// extra recipient shares must sum to 10,000 bps; extra recipients (at most
+    /// `MAX_FEE_RECIPIENTS`) each need a non-zero share.
+    pub fn set_fee_config(env: Env, admin: Address, config: FeeConfig) {
+        admin.require_auth();
+        access::require_admin(&env, &admin);
+
+        if config.extra_recipients.len() > types::MAX_FEE_RECIPIENTS {
+            panic_with_error!(&env, LiquidityPoolError::InvalidFeeConfig);
+        }
+        let mut total_bps = config.lp_bps as i128
+            + config.protocol_bps as i128
+            + config.merchant_bps as i128
+            + config.reserve_bps as i128;
+        for extra in config.extra_recipients.iter() {
+            if extra.bps == 0 {
+                panic_with_error!(&env, LiquidityPoolError::InvalidFeeConfig);
+            }
+            total_bps += extra.bps as i128;
+        }
+        if total_bps != types::TOTAL_BPS {
+            panic_with_error!(&env, LiquidityPoolError::InvalidFeeConfig);
+        }
+
+        storage::set_fee_config(&env, &config);
+        events::emit_fee_config_updated(&env, &config);
+    }
+
+    /// Add `amount` tokens from `admin` to the insurance reserve.
+    pub fn top_up_reserve(env: Env, admin: Address, amount: i128) {
+        admin.require_auth();
+        access::require_admin(&env, &admin);
+
+        if amount <= 0 {
+            panic_with_error!(&env, LiquidityPoolError::InvalidAmount);
+        }
+        Self::enter_non_reentrant(&env);
+
+        let reserve = storage::get_reserve(&env)
+            .checked_add(amount)
+            .unwrap_or_else(|| panic_with_error!(&env, LiquidityPoolError::Overflow));
+        storage::set_reserve(&env, reserve);
+
+        let token_client = token::Client::new(&env, &storage::get_token(&env));
+        token_client.transfer(&admin, &env.current_contract_address(), &amount);
+
+        events::emit_reserve_funded(&env, amount, reserve);
+        storage::bump_instance(&env);
+        Self::exit_non_reentrant(&env);
+    }
+
+    /// Send `amount` tokens of the insurance reserve to `to`.
+    pub fn withdraw_reserve(env: Env, admin: Address, to: Address, amount: i128) {
+        admin.require_auth();
+        access::require_admin(&env, &admin);
+
+        if amount <= 0 {
+            panic_with_error!(&env, LiquidityPoolError::InvalidAmount);
+        }
+        Self::enter_non_reentrant(&env);
+
+        let reserve = storage::get_reserve(&env);
+        if amount > reserve {
+            panic_with_error!(&env, LiquidityPoolError::InsufficientReserve);
+        }
+        storage::set_reserve(&env, reserve - amount);
+
+        let token_client = token::Client::new(&env, &storage::get_token(&env));
+        token_client.transfer(&env.current_contract_address(), &to, &amount);
+
+        events::emit_reserve_withdrawn(&env, &to, amount, reserve - amount);
+        storage::bump_instance(&env);
+        Self::exit_non_reentrant(&env);
+    }
+
+    /// Rename the LP share token (SEP-41 `name` / `symbol`).
+    pub fn set_share_metadata(env: Env, admin: Address, name: String, symbol: String) {
+        admin.require_auth();
+        access::require_admin(&env, &admin);
+        storage::set_share_metadata(&env, &name, &symbol);
+    }
+
     pub fn get_admin(env: Env) -> Address {
         storage::get_admin(&env)
     }
@@ -97,11 +183,17 @@ impl LiquidityPoolContract {
 
     /// Deposit `amount` tokens and receive shares representing pool ownership.
     ///
-    /// **First deposit**: shares issued == amount (1:1 ratio).
-    /// **Subsequent deposits**: `shares = (amount × total_shares) / total_pool_value`
+    /// `shares = amount × (total_shares + VIRTUAL_SHARES) / (total_pool_value + VIRTUAL_ASSETS)`
     ///
-    /// Returns the number of shares issued.
-    pub fn deposit(env: Env, provider: Address, amount: i128) -> i128 {
+    /// An empty pool takes at least `MIN_FIRST_DEPOSIT` and issues
+    /// `VIRTUAL_SHARES` shares per token, as the formula does at zero value.
+    /// The virtual offsets stop a first depositor who shrinks the share count
+    /// from inflating the share price to round later deposits down: the
+    /// virtual shares keep nearly all of any donation made to the pool.
+    ///
+    /// Panics with `SlippageExceeded` if fewer than `min_shares_out` shares
+    /// would be issued. Returns the number of shares issued.
+    pub fn deposit(env: Env, provider: Address, amount: i128, min_shares_out: i128) -> i128 {
         provider.require_auth();
         Self::require_not_paused(&env);
 
@@ -115,21 +207,30 @@ impl LiquidityPoolContract {
         let total_shares = storage::get_total_shares(&env);
         let total_liquidity = storage::get_total_liquidity(&env);
 
+        if total_shares == 0 && amount < types::MIN_FIRST_DEPOSIT {
+            panic_with_error!(&env, LiquidityPoolError::FirstDepositTooSmall);
+        }
+
         // Calculate shares to issue
-        let shares_issued = if total_shares == 0 || total_liquidity == 0 {
-            // First deposit: 1:1 ratio
+        let shares_issued = if total_shares == 0 {
+            // A pool that predates the decimals offset takes it on once nobody
+            // holds its 1:1 shares any more
+            storage::set_share_decimals_offset(&env, types::SHARE_DECIMALS_OFFSET);
+            // Empty pool: VIRTUAL_SHARES per token (any rounding dust left behind goes along)
             amount
+                .checked_mul(types::VIRTUAL_SHARES)
+                .unwrap_or_else(|| panic_with_error!(&env, LiquidityPoolError::Overflow))
         } else {
             // Subsequent deposits: proportional to current pool value
-            amount
-                .checked_mul(total_shares)
-                .and_then(|v| v.checked_div(total_liquidity))
-                .unwrap_or_else(|| panic_with_error!(&env, LiquidityPoolError::Overflow))
+            Self::shares_for_amount(&env, amount, total_shares, total_liquidity)
         };
 
         if shares_issued <= 0 {
             panic_with_error!(&env, LiquidityPoolError::InvalidAmount);
         }
+        if shares_issued < min_shares_out {
+            panic_with_error!(&env, LiquidityPoolError::SlippageExceeded);
+        }
 
         // Update state
         let new_shares = storage::get_lp_shares(&env, &provider)
@@ -153,6 +254,7 @@ impl LiquidityPoolContract {
         token_client.transfer(&provider, &env.current_contract_address(), &amount);
 
         events::emit_liquidity_deposited(&env, &provider, amount, shares_issued);
+        events::emit_share_mint(&env, &provider, shares_issued);
         storage::bump_instance(&env);
         Self::exit_non_reentrant(&env);
 
@@ -161,7 +263,7 @@ impl LiquidityPoolContract {
 
     /// Burn `shares` and return the proportional token amount to `provider`.
     ///
-    /// `amount = (shares × total_pool_value) / total_shares`
+    /// `amount = shares × (total_pool_value + VIRTUAL_ASSETS) / (total_shares + VIRTUAL_SHARES)`
     ///
     /// Returns the number of tokens returned.
     pub fn withdraw(env: Env, provider: Address, shares: i128) -> i128 {
@@ -179,6 +281,11 @@ impl LiquidityPoolContract {
             panic_with_error!(&env, LiquidityPoolError::InsufficientShares);
         }
 
+        // Queued requests are filled first; instant exits would jump the queue.
+        if storage::get_queue_head(&env) < storage::get_queue_tail(&env) {
+            panic_with_error!(&env, LiquidityPoolError::WithdrawalQueuePending);
+        }
+
         let total_shares = storage::get_total_shares(&env);
         if total_shares == 0 {
             panic_with_error!(&env, LiquidityPoolError::ZeroTotalShares);
@@ -191,10 +298,7 @@ impl LiquidityPoolContract {
             .unwrap_or_else(|| panic_with_error!(&env, LiquidityPoolError::Underflow));
 
         // Calculate withdrawal amount proportionally
-        let amount_returned = shares
-            .checked_mul(total_liquidity)
-            .and_then(|v| v.checked_div(total_shares))
-            .unwrap_or_else(|| panic_with_error!(&env, LiquidityPoolError::Overflow));
+        let amount_returned = Self::amount_for_shares(&env, shares, total_shares, total_liquidity);
 
         if amount_returned > available_liquidity {
             panic_with_error!(&env, LiquidityPoolError::InsufficientLiquidity);
@@ -218,6 +322,7 @@ impl LiquidityPoolContract {
         storage::set_total_liquidity(&env, new_total_liquidity);
 
         events::emit_liquidity_withdrawn(&env, &provider, shares, amount_returned);
+        events::emit_share_burn(&env, &provider, shares);
         // Transfer tokens back to provider after state effects.
         let token = storage::get_token(&env);
         let token_client = token::Client::new(&env, &token);
@@ -228,6 +333,108 @@ impl LiquidityPoolContract {
         amount_returned
     }
 
+    /// Queue a withdrawal of `shares` for when liquidity is free.
+    ///
+    /// The shares move from the provider to the pool's own balance; they
+    /// keep sharing in interest and losses until filled. Requests are filled
+    /// in FIFO order, possibly in several parts, at the share price in effect
+    /// when each part is filled: after every repayment or guarantee the pool
+    /// receives, or through `process_withdrawal_queue`. Filled tokens are
+    /// credited to the provider, who collects them with `claim_withdrawal`.
+    ///
+    /// Returns the request id.
+    pub fn request_withdrawal(env: Env, provider: Address, shares: i128) -> u64 {
+        provider.require_auth();
+        Self::require_not_paused(&env);
+
+        if shares <= 0 {
+            panic_with_error!(&env, LiquidityPoolError::InvalidAmount);
+        }
+
+        Self::enter_non_reentrant(&env);
+
+        // Escrow: the shares move to the pool's own share balance.
+        Self::move_shares(&env, &provider, &env.current_contract_address(), shares);
+
+        let pending = storage::get_pending_shares(&env, &provider)
+            .checked_add(shares)
+            .unwrap_or_else(|| panic_with_error!(&env, LiquidityPoolError::Overflow));
+        storage::set_pending_shares(&env, &provider, pending);
+
+        let queued = storage::get_queued_shares(&env)
+            .checked_add(shares)
+            .unwrap_or_else(|| panic_with_error!(&env, LiquidityPoolError::Overflow));
+        storage::set_queued_shares(&env, queued);
+
+        let request_id = storage::get_queue_tail(&env);
+        let request = WithdrawalRequest {
+            provider: provider.clone(),
+            shares,
+            requested_at: env.ledger().timestamp(),
+        };
+        storage::set_withdrawal_request(&env, request_id, &request);
+        let new_tail = request_id
+            .checked_add(1)
+            .unwrap_or_else(|| panic_with_error!(&env, LiquidityPoolError::Overflow));
+        storage::set_queue_tail(&env, new_tail);
+
+        events::emit_withdrawal_queued(&env, &provider, request_id, shares);
+        storage::bump_instance(&env);
+        Self::exit_non_reentrant(&env);
+
+        request_id
+    }
+
+    /// Fill queued withdrawals from available liquidity, oldest first.
+    /// Anyone may call this. At most `limit` (capped at `MAX_QUEUE_BATCH`)
+    /// requests are completed; processing stops at the first request that
+    /// can only be filled in part.
+    ///
+    /// Returns the number of requests completed.
+    pub fn process_withdrawal_queue(env: Env, limit: u32) -> u32 {
+        Self::require_not_paused(&env);
+        Self::enter_non_reentrant(&env);
+
+        let completed = Self::fill_withdrawal_queue(&env, limit.min(types::MAX_QUEUE_BATCH));
+
+        storage::bump_instance(&env);
+        Self::exit_non_reentrant(&env);
+        completed
+    }
+
+    /// Transfer the tokens of `provider`'s filled withdrawal requests to them.
+    ///
+    /// Fills only credit the provider, so an account that cannot receive
+    /// tokens never blocks the queue or the repayments that drive it.
+    ///
+    /// Returns the amount transferred.
+    pub fn claim_withdrawal(env: Env, provider: Address) -> i128 {
+        provider.require_auth();
+        Self::require_not_paused(&env);
+
+        let amount = storage::get_claimable(&env, &provider);
+        if amount <= 0 {
+            panic_with_error!(&env, LiquidityPoolError::NothingToClaim);
+        }
+
+        Self::enter_non_reentrant(&env);
+
+        storage::set_claimable(&env, &provider, 0);
+        let claimable_total = storage::get_claimable_total(&env)
+            .checked_sub(amount)
+            .unwrap_or_else(|| panic_with_error!(&env, LiquidityPoolError::Underflow));
+        storage::set_claimable_total(&env, claimable_total);
+
+        let token_client = token::Client::new(&env, &storage::get_token(&env));
+        token_client.transfer(&env.current_contract_address(), &provider, &amount);
+
+        events::emit_withdrawal_claimed(&env, &provider, amount);
+        storage::bump_instance(&env);
+        Self::exit_non_reentrant(&env);
+
+        amount
+    }
+
     // -------------------------------------------------------------------------
     // CreditLine Operations (access-restricted)
     // -------------------------------------------------------------------------
@@ -308,12 +515,14 @@ impl LiquidityPoolContract {
         if interest > 0 {
             Self::distribute_interest_internal(&env, interest);
         }
+        Self::fill_withdrawal_queue(&env, types::AUTO_FILL_LIMIT);
         Self::exit_non_reentrant(&env);
     }
 
     /// Receive a forfeited guarantee on loan default.
-    /// The amount offsets the loss: it is added back to total_liquidity
-    /// and reduces locked_liquidity by the same amount (partial recovery).
+    /// The guarantee returns part of the defaulted loan's capital in cash: it
+    /// reduces locked_liquidity by the same amount, while total_liquidity is
+    /// unchanged (the capital was already counted while lent out).
     pub fn receive_guarantee(env: Env, creditline: Address, amount: i128) {
         creditline.require_auth();
         access::require_creditline(&env, &creditline);
@@ -324,9 +533,9 @@ impl LiquidityPoolContract {
         }
         Self::enter_non_reentrant(&env);
 
-        // The defaulted loan principal stays "locked" — the guarantee partially
-        // covers the loss.  We reduce locked_liquidity by the guarantee amount
-        // and add it back to total_liquidity (net pool recovers that portion).
+        // The guarantee partially covers the loss.  We reduce locked_liquidity by
+        // the guarantee amount, now held as free tokens again; the uncovered rest
+        // is realised by `write_off`.
         let locked = storage::get_locked_liquidity(&env);
         let recovered = amount.min(locked); // can't recover more than locked
         let new_locked = locked
@@ -334,18 +543,151 @@ impl LiquidityPoolContract {
             .unwrap_or_else(|| panic_with_error!(&env, LiquidityPoolError::Underflow));
         storage::set_locked_liquidity(&env, new_locked);
 
+        let token = storage::get_token(&env);
+        let token_client = token::Client::new(&env, &token);
+        // Transfer only the recovered amount — keeps accounting consistent (H-3).
+        token_client.transfer(&creditline, &env.current_contract_address(), &recovered);
+
+        events::emit_guarantee_received(&env, &creditline, recovered);
+        Self::fill_withdrawal_queue(&env, types::AUTO_FILL_LIMIT);
+        Self::exit_non_reentrant(&env);
+    }
+
+    /// Receive a payment collected on a defaulted loan.
+    /// Treated as loss recovery, not interest: the full amount is added to
+    /// total_liquidity.  The defaulted loan's capital was already released by
+    /// `write_off`, so locked_liquidity (other loans' capital) is untouched.
+    pub fn receive_recovery(env: Env, creditline: Address, amount: i128) {
+        creditline.require_auth();
+        access::require_creditline(&env, &creditline);
+        Self::require_not_paused(&env);
+
+        if amount <= 0 {
+            panic_with_error!(&env, LiquidityPoolError::InvalidAmount);
+        }
+        Self::enter_non_reentrant(&env);
+
         let total_liquidity = storage::get_total_liquidity(&env);
         let new_total = total_liquidity
-            .checked_add(recovered)
+            .checked_add(amount)
             .unwrap_or_else(|| panic_with_error!(&env, LiquidityPoolError::Overflow));
         storage::set_total_liquidity(&env, new_total);
 
         let token = storage::get_token(&env);
         let token_client = token::Client::new(&env, &token);
-        // Transfer only the recovered amount — keeps accounting consistent (H-3).
-        token_client.transfer(&creditline, &env.current_contract_address(), &recovered);
+        token_client.transfer(&creditline, &env.current_contract_address(), &amount);
 
-        events::emit_guarantee_received(&env, &creditline, recovered);
+        events::emit_recovery_received(&env, &creditline, amount);
+        Self::exit_non_reentrant(&env);
+    }
+
+    /// Realise the principal of a defaulted loan that its guarantee did not
+    /// cover.  The insurance reserve absorbs it first: the absorbed part moves
+    /// from the reserve into LP capital to replace the lost principal.  Only
+    /// the rest is removed from total_liquidity, lowering the share price, and
+    /// added to the cumulative `total_losses`.  Releases `principal_lost` of
+    /// locked_liquidity and fails with `WriteOffExceedsLocked` if less is
+    /// locked.  No tokens move.
+    pub fn write_off(env: Env, creditline: Address, principal_lost: i128) {
+        creditline.require_auth();
+        access::require_creditline(&env, &creditline);
+        Self::require_not_paused(&env);
+
+        if principal_lost <= 0 {
+            panic_with_error!(&env, LiquidityPoolError::InvalidAmount);
+        }
+        Self::enter_non_reentrant(&env);
+
+        let locked = storage::get_locked_liquidity(&env);
+        if principal_lost > locked {
+            panic_with_error!(&env, LiquidityPoolError::WriteOffExceedsLocked);
+        }
+        let new_locked = locked
+            .checked_sub(principal_lost)
+            .unwrap_or_else(|| panic_with_error!(&env, LiquidityPoolError::Underflow));
+        storage::set_locked_liquidity(&env, new_locked);
+
+        Self::realise_loss(&env, &creditline, principal_lost);
+        Self::exit_non_reentrant(&env);
+    }
+
+    /// Book `amount` of released capital that did not come back in cash.  The
+    /// insurance reserve absorbs it first; the rest leaves total_liquidity and
+    /// is added to `total_losses`.
+    fn realise_loss(env: &Env, creditline: &Address, amount: i128) {
+        let reserve = storage::get_reserve(env);
+        let absorbed = amount.min(reserve);
+        if absorbed > 0 {
+            let new_reserve = reserve
+                .checked_sub(absorbed)
+                .unwrap_or_else(|| panic_with_error!(env, LiquidityPoolError::Underflow));
+            storage::set_reserve(env, new_reserve);
+            events::emit_reserve_absorbed_loss(env, creditline, absorbed, new_reserve);
+        }
+
+        let lost = amount
+            .checked_sub(absorbed)
+            .unwrap_or_else(|| panic_with_error!(env, LiquidityPoolError::Underflow));
+        let new_total = storage::get_total_liquidity(env)
+            .checked_sub(lost)
+            .unwrap_or_else(|| panic_with_error!(env, LiquidityPoolError::Underflow));
+        storage::set_total_liquidity(env, new_total);
+
+        let total_losses = storage::get_total_losses(env)
+            .checked_add(lost)
+            .unwrap_or_else(|| panic_with_error!(env, LiquidityPoolError::Overflow));
+        storage::set_total_losses(env, total_losses);
+
+        events::emit_loss_written_off(env, creditline, lost, total_losses);
+    }
+
+    /// Sell a loan receivable to `buyer` for `price`. The creditline assigns
+    /// the loan's future cash flows to the buyer (the pool, as current holder,
+    /// authorises by being the caller) and reports the pool capital still in
+    /// the loan, which is released from `locked_liquidity`. The price replaces
+    /// that capital in `total_liquidity`; a shortfall is realised like a
+    /// `write_off`, reserve first. The proceeds then fill queued withdrawals.
+    pub fn sell_receivable(env: Env, admin: Address, loan_id: u64, buyer: Address, price: i128) {
+        admin.require_auth();
+        access::require_admin(&env, &admin);
+        Self::require_not_paused(&env);
+        buyer.require_auth();
+
+        if price <= 0 {
+            panic_with_error!(&env, LiquidityPoolError::InvalidAmount);
+        }
+        let creditline = storage::get_creditline(&env)
+            .unwrap_or_else(|| panic_with_error!(&env, LiquidityPoolError::NotCreditLine));
+        Self::enter_non_reentrant(&env);
+
+        let pool_capital: i128 = env.invoke_contract(
+            &creditline,
+            &Symbol::new(&env, "transfer_receivable"),
+            (loan_id, buyer.clone()).into_val(&env),
+        );
+
+        let token = storage::get_token(&env);
+        let token_client = token::Client::new(&env, &token);
+        token_client.transfer(&buyer, &env.current_contract_address(), &price);
+
+        let locked = storage::get_locked_liquidity(&env);
+        let released = pool_capital.min(locked);
+        let new_locked = locked
+            .checked_sub(released)
+            .unwrap_or_else(|| panic_with_error!(&env, LiquidityPoolError::Underflow));
+        storage::set_locked_liquidity(&env, new_locked);
+
+        if price >= released {
+            let total_liquidity = storage::get_total_liquidity(&env)
+                .checked_add(price - released)
+                .unwrap_or_else(|| panic_with_error!(&env, LiquidityPoolError::Overflow));
+            storage::set_total_liquidity(&env, total_liquidity);
+        } else {
+            Self::realise_loss(&env, &creditline, released - price);
+        }
+
+        events::emit_receivable_sold(&env, loan_id, &buyer, released, price);
+        Self::fill_withdrawal_queue(&env, types::AUTO_FILL_LIMIT);
         Self::exit_non_reentrant(&env);
     }
 
@@ -358,7 +700,7 @@ impl LiquidityPoolContract {
         caller.require_auth();
         Self::require_not_paused(&env);
         let is_admin = storage::get_admin(&env) == caller;
-        let is_creditline = storage::get_creditline(&env).map_or(false, |cl| cl == caller);
+        let is_creditline = storage::get_creditline(&env).is_some_and(|cl| cl == caller);
         if !is_admin && !is_creditline {
             panic_with_error!(&env, LiquidityPoolError::NotCreditLine);
         }
@@ -372,27 +714,46 @@ impl LiquidityPoolContract {
             panic_with_error!(env, LiquidityPoolError::InvalidAmount);
         }
 
-        // 85% stays in the pool → increases share value
-        let lp_amount = interest_amount
-            .checked_mul(types::LP_FEE_BPS)
-            .and_then(|v| v.checked_div(types::TOTAL_BPS))
-            .unwrap_or_else(|| panic_with_error!(env, LiquidityPoolError::Overflow));
+        let config = storage::get_fee_config(env);
 
-        // 10% → treasury
-        let protocol_amount = interest_amount
-            .checked_mul(types::PROTOCOL_FEE_BPS)
-            .and_then(|v| v.checked_div(types::TOTAL_BPS))
-            .unwrap_or_else(|| panic_with_error!(env, LiquidityPoolError::Overflow));
+        // LP share (85% by default) stays in the pool → increases share value
+        let lp_amount = Self::fee_share(env, interest_amount, config.lp_bps);
+
+        // Protocol share (10% by default) → treasury
+        let protocol_amount = Self::fee_share(env, interest_amount, config.protocol_bps);
+
+        let token = storage::get_token(env);
+        let token_client = token::Client::new(env, &token);
+
+        // Extra recipients (e.g. an insurance reserve) are paid their share
+        let mut extras_amount = 0i128;
+        for extra in config.extra_recipients.iter() {
+            let amount = Self::fee_share(env, interest_amount, extra.bps);
+            if amount > 0 {
+                token_client.transfer(&env.current_contract_address(), &extra.recipient, &amount);
+                events::emit_fee_paid(env, &extra.recipient, amount);
+            }
+            extras_amount += amount;
+        }
 
-        // 5% → merchant fund (use remainder to avoid rounding dust)
+        // Reserve share (0% by default) stays in the pool, outside LP capital
+        let reserve_amount = Self::fee_share(env, interest_amount, config.reserve_bps);
+        if reserve_amount > 0 {
+            let reserve = storage::get_reserve(env)
+                .checked_add(reserve_amount)
+                .unwrap_or_else(|| panic_with_error!(env, LiquidityPoolError::Overflow));
+            storage::set_reserve(env, reserve);
+            events::emit_reserve_funded(env, reserve_amount, reserve);
+        }
+
+        // Merchant share (5% by default) → merchant fund (use remainder to avoid rounding dust)
         let merchant_amount = interest_amount
             .checked_sub(lp_amount)
             .and_then(|v| v.checked_sub(protocol_amount))
+            .and_then(|v| v.checked_sub(extras_amount))
+            .and_then(|v| v.checked_sub(reserve_amount))
             .unwrap_or_else(|| panic_with_error!(env, LiquidityPoolError::Underflow));
 
-        let token = storage::get_token(env);
-        let token_client = token::Client::new(env, &token);
-
         // Transfer protocol fee to treasury (if configured)
         if protocol_amount > 0 {
             if let Some(treasury) = storage::get_treasury(env) {
@@ -430,6 +791,128 @@ impl LiquidityPoolContract {
         );
     }
 
+    /// `bps` of `amount`, rounded down.
+    fn fee_share(env: &Env, amount: i128, bps: u32) -> i128 {
+        amount
+            .checked_mul(bps as i128)
+            .and_then(|v| v.checked_div(types::TOTAL_BPS))
+            .unwrap_or_else(|| panic_with_error!(env, LiquidityPoolError::Overflow))
+    }
+
+    /// Fill up to `limit` queued withdrawals from available liquidity.
+    /// Each part burns the escrowed shares at the current share price and
+    /// credits the tokens to the provider's claimable balance; a request
+    /// larger than the available liquidity is filled in part and stays at the
+    /// head of the queue. No tokens move, so filling never fails on a
+    /// provider that cannot receive them.
+    fn fill_withdrawal_queue(env: &Env, limit: u32) -> u32 {
+        let mut head = storage::get_queue_head(env);
+        let tail = storage::get_queue_tail(env);
+        let token_client = token::Client::new(env, &storage::get_token(env));
+        let escrow = env.current_contract_address();
+        let mut completed = 0;
+
+        while head < tail && completed < limit {
+            let mut request = match storage::get_withdrawal_request(env, head) {
+                Some(request) => request,
+                None => {
+                    head += 1;
+                    continue;
+                }
+            };
+
+            let total_shares = storage::get_total_shares(env);
+            let total_liquidity = storage::get_total_liquidity(env);
+            // Never pay out more than the pool actually holds outside the
+            // reserve and unclaimed fills, whatever the accounting says is free.
+            let held = token_client
+                .balance(&escrow)
+                .saturating_sub(storage::get_reserve(env))
+                .saturating_sub(storage::get_claimable_total(env));
+            let available = total_liquidity
+                .saturating_sub(storage::get_locked_liquidity(env))
+                .min(held);
+            if total_shares <= 0 || total_liquidity <= 0 || available <= 0 {
+                break;
+            }
+
+            let full_amount =
+                Self::amount_for_shares(env, request.shares, total_shares, total_liquidity);
+            let (shares_burned, amount_returned) = if full_amount <= available {
+                (request.shares, full_amount)
+            } else {
+                let shares = Self::shares_for_amount(env, available, total_shares, total_liquidity);
+                let amount = Self::amount_for_shares(env, shares, total_shares, total_liquidity);
+                (shares, amount)
+            };
+            if shares_burned <= 0 || (shares_burned < request.shares && amount_returned <= 0) {
+                break;
+            }
+
+            let new_total_shares = total_shares
+                .checked_sub(shares_burned)
+                .unwrap_or_else(|| panic_with_error!(env, LiquidityPoolError::Underflow));
+            storage::set_total_shares(env, new_total_shares);
+
+            let new_total_liquidity = total_liquidity
+                .checked_sub(amount_returned)
+                .unwrap_or_else(|| panic_with_error!(env, LiquidityPoolError::Underflow));
+            storage::set_total_liquidity(env, new_total_liquidity);
+
+            let queued = storage::get_queued_shares(env)
+                .checked_sub(shares_burned)
+                .unwrap_or_else(|| panic_with_error!(env, LiquidityPoolError::Underflow));
+            storage::set_queued_shares(env, queued);
+
+            let escrowed = storage::get_lp_shares(env, &escrow)
+                .checked_sub(shares_burned)
+                .unwrap_or_else(|| panic_with_error!(env, LiquidityPoolError::Underflow));
+            storage::set_lp_shares(env, &escrow, escrowed);
+
+            let pending = storage::get_pending_shares(env, &request.provider)
+                .checked_sub(shares_burned)
+                .unwrap_or_else(|| panic_with_error!(env, LiquidityPoolError::Underflow));
+            storage::set_pending_shares(env, &request.provider, pending);
+
+            request.shares = request
+                .shares
+                .checked_sub(shares_burned)
+                .unwrap_or_else(|| panic_with_error!(env, LiquidityPoolError::Underflow));
+
+            if amount_returned > 0 {
+                let claimable = storage::get_claimable(env, &request.provider)
+                    .checked_add(amount_returned)
+                    .unwrap_or_else(|| panic_with_error!(env, LiquidityPoolError::Overflow));
+                storage::set_claimable(env, &request.provider, claimable);
+
+                let claimable_total = storage::get_claimable_total(env)
+                    .checked_add(amount_returned)
+                    .unwrap_or_else(|| panic_with_error!(env, LiquidityPoolError::Overflow));
+                storage::set_claimable_total(env, claimable_total);
+            }
+            events::emit_share_burn(env, &escrow, shares_burned);
+            events::emit_withdrawal_filled(
+                env,
+                &request.provider,
+                head,
+                shares_burned,
+                amount_returned,
+                request.shares,
+            );
+
+            if request.shares > 0 {
+                storage::set_withdrawal_request(env, head, &request);
+                break;
+            }
+            storage::remove_withdrawal_request(env, head);
+            head += 1;
+            completed += 1;
+        }
+
+        storage::set_queue_head(env, head);
+        completed
+    }
+
     // -------------------------------------------------------------------------
     // Queries
     // -------------------------------------------------------------------------
@@ -440,25 +923,40 @@ impl LiquidityPoolContract {
         let available_liquidity = total_liquidity.saturating_sub(locked_liquidity);
         let total_shares = storage::get_total_shares(&env);
 
-        // Share price in basis points: (total_liquidity × 10000) / total_shares
+        // Share price in basis points: (total_liquidity × 10000 × virtual shares) / total_shares
         let share_price = if total_shares == 0 {
             types::TOTAL_BPS // Default: 1.00 expressed as 10000 bps
         } else {
             total_liquidity
-                .checked_mul(types::TOTAL_BPS)
+                .checked_mul(types::TOTAL_BPS * Self::virtual_shares(&env))
                 .and_then(|v| v.checked_div(total_shares))
                 .unwrap_or(types::TOTAL_BPS)
         };
 
+        // Reserve coverage of capital lent out, in basis points
+        let reserve_balance = storage::get_reserve(&env);
+        let reserve_coverage_bps = if locked_liquidity <= 0 {
+            0
+        } else {
+            reserve_balance
+                .checked_mul(types::TOTAL_BPS)
+                .and_then(|v| v.checked_div(locked_liquidity))
+                .unwrap_or(0)
+        };
+
         PoolStats {
             total_liquidity,
             locked_liquidity,
             available_liquidity,
             total_shares,
             share_price,
+            total_losses: storage::get_total_losses(&env),
+            reserve_balance,
+            reserve_coverage_bps,
         }
     }
 
+    /// Same as the share token's `balance`.
     pub fn get_lp_shares(env: Env, provider: Address) -> i128 {
         storage::bump_lp_shares(&env, &provider);
         storage::get_lp_shares(&env, &provider)
@@ -472,11 +970,58 @@ impl LiquidityPoolContract {
         }
         let total_liquidity = storage::get_total_liquidity(&env);
         shares
-            .checked_mul(total_liquidity)
-            .and_then(|v| v.checked_div(total_shares))
+            .checked_mul(total_liquidity + types::VIRTUAL_ASSETS)
+            .and_then(|v| v.checked_div(total_shares + Self::virtual_shares(&env)))
             .unwrap_or(0)
     }
 
+    /// Return a queued withdrawal request, or `None` once it has been filled.
+    pub fn get_withdrawal_request(env: Env, request_id: u64) -> Option<WithdrawalRequest> {
+        storage::get_withdrawal_request(&env, request_id)
+    }
+
+    /// Number of queued requests ahead of `request_id` (0 = next to be filled).
+    pub fn get_withdrawal_queue_position(env: Env, request_id: u64) -> u64 {
+        if storage::get_withdrawal_request(&env, request_id).is_none() {
+            panic_with_error!(&env, LiquidityPoolError::WithdrawalRequestNotFound);
+        }
+        request_id
+            .checked_sub(storage::get_queue_head(&env))
+            .unwrap_or_else(|| panic_with_error!(&env, LiquidityPoolError::Underflow))
+    }
+
+    /// Number of requests waiting in the withdrawal queue.
+    pub fn get_withdrawal_queue_length(env: Env) -> u64 {
+        storage::get_queue_tail(&env)
+            .checked_sub(storage::get_queue_head(&env))
+            .unwrap_or_else(|| panic_with_error!(&env, LiquidityPoolError::Underflow))
+    }
+
+    /// Total shares escrowed in the withdrawal queue.
+    pub fn get_queued_shares(env: Env) -> i128 {
+        storage::get_queued_shares(&env)
+    }
+
+    /// Shares `provider` has waiting in the withdrawal queue.
+    pub fn get_pending_withdrawal(env: Env, provider: Address) -> i128 {
+        storage::get_pending_shares(&env, &provider)
+    }
+
+    /// Tokens of `provider`'s filled withdrawals waiting for `claim_withdrawal`.
+    pub fn get_claimable_withdrawal(env: Env, provider: Address) -> i128 {
+        storage::get_claimable(&env, &provider)
+    }
+
+    /// Tokens `provider`'s queued shares are worth at the current share price.
+    pub fn get_pending_withdrawal_amount(env: Env, provider: Address) -> i128 {
+        let shares = storage::get_pending_shares(&env, &provider);
+        Self::calculate_withdrawal(env, shares)
+    }
+
+    pub fn get_fee_config(env: Env) -> FeeConfig {
+        storage::get_fee_config(&env)
+    }
+
     pub fn get_token(env: Env) -> Address {
         storage::get_token(&env)
     }
@@ -497,6 +1042,38 @@ impl LiquidityPoolContract {
     // Internal helpers
     // -------------------------------------------------------------------------
 
+    /// Shares worth `amount` tokens, rounded down in the pool's favour.
+    fn shares_for_amount(
+        env: &Env,
+        amount: i128,
+        total_shares: i128,
+        total_liquidity: i128,
+    ) -> i128 {
+        amount
+            .checked_mul(total_shares + Self::virtual_shares(env))
+            .and_then(|v| v.checked_div(total_liquidity + types::VIRTUAL_ASSETS))
+            .unwrap_or_else(|| panic_with_error!(env, LiquidityPoolError::Overflow))
+    }
+
+    /// Tokens `shares` are worth, rounded down in the pool's favour.
+    fn amount_for_shares(
+        env: &Env,
+        shares: i128,
+        total_shares: i128,
+        total_liquidity: i128,
+    ) -> i128 {
+        shares
+            .checked_mul(total_liquidity + types::VIRTUAL_ASSETS)
+            .and_then(|v| v.checked_div(total_shares + Self::virtual_shares(env)))
+            .unwrap_or_else(|| panic_with_error!(env, LiquidityPoolError::Overflow))
+    }
+
+    /// Virtual shares for this pool's decimals offset: `VIRTUAL_SHARES`, or
+    /// one for a pool still holding 1:1 shares from before the offset.
+    fn virtual_shares(env: &Env) -> i128 {
+        10_i128.pow(storage::get_share_decimals_offset(env))
+    }
+
     fn require_not_paused(env: &Env) {
         if storage::is_paused(env) {
             panic_with_error!(env, LiquidityPoolError::ContractPaused);
@@ -513,6 +1090,125 @@ impl LiquidityPoolContract {
     fn exit_non_reentrant(env: &Env) {
         storage::set_reentrancy_locked(env, false);
     }
+
+    /// Move `amount` shares between holders and emit the SEP-41 `transfer` event.
+    fn move_shares(env: &Env, from: &Address, to: &Address, amount: i128) {
+        let from_balance = storage::get_lp_shares(env, from);
+        if from_balance < amount {
+            panic_with_error!(env, LiquidityPoolError::InsufficientShares);
+        }
+        let new_from_balance = from_balance
+            .checked_sub(amount)
+            .unwrap_or_else(|| panic_with_error!(env, LiquidityPoolError::Underflow));
+        storage::set_lp_shares(env, from, new_from_balance);
+        storage::bump_lp_shares(env, from);
+
+        let to_balance = storage::get_lp_shares(env, to)
+            .checked_add(amount)
+            .unwrap_or_else(|| panic_with_error!(env, LiquidityPoolError::Overflow));
+        storage::set_lp_shares(env, to, to_balance);
+        storage::bump_lp_shares(env, to);
+
+        events::emit_share_transfer(env, from, to, amount);
+    }
+
+    fn spend_allowance(env: &Env, from: &Address, spender: &Address, amount: i128) {
+        let allowance = storage::get_allowance(env, from, spender);
+        if allowance.amount < amount {
+            panic_with_error!(env, LiquidityPoolError::InsufficientAllowance);
+        }
+        if amount > 0 {
+            let remaining = types::AllowanceValue {
+                amount: allowance
+                    .amount
+                    .checked_sub(amount)
+                    .unwrap_or_else(|| panic_with_error!(env, LiquidityPoolError::Underflow)),
+                expiration_ledger: allowance.expiration_ledger,
+            };
+            storage::set_allowance(env, from, spender, &remaining);
+        }
+    }
+
+    fn check_nonnegative(env: &Env, amount: i128) {
+        if amount < 0 {
+            panic_with_error!(env, LiquidityPoolError::InvalidAmount);
+        }
+    }
+}
+
+/// SEP-41 interface over the LP shares.
+///
+/// Shares are the `LPSHRS` balances themselves: deposits mint them, withdrawals
+/// and filled queue requests burn them, and holders can move them freely
+/// between addresses. They can only be destroyed by redeeming them, so `burn`
+/// and `burn_from` fail with `BurnNotSupported`. `decimals` is the underlying
+/// token's plus the pool's decimals offset (`SHARE_DECIMALS_OFFSET`), so one
+/// whole share starts out worth one token.
+#[contractimpl]
+impl TokenInterface for LiquidityPoolContract {
+    fn allowance(env: Env, from: Address, spender: Address) -> i128 {
+        storage::get_allowance(&env, &from, &spender).amount
+    }
+
+    fn approve(env: Env, from: Address, spender: Address, amount: i128, expiration_ledger: u32) {
+        from.require_auth();
+        Self::check_nonnegative(&env, amount);
+        if amount > 0 && expiration_ledger < env.ledger().sequence() {
+            panic_with_error!(&env, LiquidityPoolError::InvalidExpirationLedger);
+        }
+
+        let allowance = types::AllowanceValue {
+            amount,
+            expiration_ledger,
+        };
+        storage::set_allowance(&env, &from, &spender, &allowance);
+        storage::bump_instance(&env);
+        events::emit_share_approve(&env, &from, &spender, amount, expiration_ledger);
+    }
+
+    fn balance(env: Env, id: Address) -> i128 {
+        storage::get_lp_shares(&env, &id)
+    }
+
+    fn transfer(env: Env, from: Address, to: Address, amount: i128) {
+        from.require_auth();
+        Self::require_not_paused(&env);
+        Self::check_nonnegative(&env, amount);
+
+        Self::move_shares(&env, &from, &to, amount);
+        storage::bump_instance(&env);
+    }
+
+    fn transfer_from(env: Env, spender: Address, from: Address, to: Address, amount: i128) {
+        spender.require_auth();
+        Self::require_not_paused(&env);
+        Self::check_nonnegative(&env, amount);
+
+        Self::spend_allowance(&env, &from, &spender, amount);
+        Self::move_shares(&env, &from, &to, amount);
+        storage::bump_instance(&env);
+    }
+
+    fn burn(env: Env, _from: Address, _amount: i128) {
+        panic_with_error!(&env, LiquidityPoolError::BurnNotSupported);
+    }
+
+    fn burn_from(env: Env, _spender: Address, _from: Address, _amount: i128) {
+        panic_with_error!(&env, LiquidityPoolError::BurnNotSupported);
+    }
+
+    fn decimals(env: Env) -> u32 {
+        token::Client::new(&env, &storage::get_token(&env)).decimals()
+            + storage::get_share_decimals_offset(&env)
+    }
+
+    fn name(env: Env) -> String {
+        storage::get_share_name(&env)
+    }
+
+    fn symbol(env: Env) -> String {
+        storage::get_share_symbol(&env)
+    }
 }
 
 #[cfg(test)]
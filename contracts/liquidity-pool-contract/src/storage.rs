@@ -1,4 +1,9 @@
-use soroban_sdk::{symbol_short, Address, Env, Symbol};
+use soroban_sdk::{symbol_short, Address, Env, String, Symbol, Vec};
+
+use crate::types::{
+    AllowanceValue, FeeConfig, WithdrawalRequest, LP_FEE_BPS, MERCHANT_FEE_BPS, PROTOCOL_FEE_BPS,
+    SHARE_NAME, SHARE_SYMBOL,
+};
 
 // Instance storage keys
 pub const ADMIN_KEY: Symbol = symbol_short!("ADMIN");
@@ -6,14 +11,30 @@ pub const TOKEN_KEY: Symbol = symbol_short!("TOKEN");
 pub const TOTAL_SHARES_KEY: Symbol = symbol_short!("TOTSHRS");
 pub const TOTAL_LIQUIDITY_KEY: Symbol = symbol_short!("TOTLIQ");
 pub const LOCKED_LIQUIDITY_KEY: Symbol = symbol_short!("LCKDLIQ");
+pub const TOTAL_LOSSES_KEY: Symbol = symbol_short!("TOTLOSS");
 pub const CREDITLINE_KEY: Symbol = symbol_short!("CRDTLIN");
 pub const TREASURY_KEY: Symbol = symbol_short!("TREASURY");
 pub const MERCHANT_FUND_KEY: Symbol = symbol_short!("MRCHFND");
 pub const REENTRANCY_LOCK_KEY: Symbol = symbol_short!("LOCKED");
 pub const PAUSED_KEY: Symbol = symbol_short!("PAUSED");
+pub const QUEUE_HEAD_KEY: Symbol = symbol_short!("WQHEAD");
+pub const QUEUE_TAIL_KEY: Symbol = symbol_short!("WQTAIL");
+pub const QUEUED_SHARES_KEY: Symbol = symbol_short!("WQSHRS");
+pub const SHARE_NAME_KEY: Symbol = symbol_short!("SHRNAME");
+pub const SHARE_SYMBOL_KEY: Symbol = symbol_short!("SHRSYM");
+pub const FEE_CONFIG_KEY: Symbol = symbol_short!("FEECFG");
+pub const RESERVE_KEY: Symbol = symbol_short!("RESERVE");
+pub const CLAIMABLE_TOTAL_KEY: Symbol = symbol_short!("WQCLMTOT");
+pub const SHARE_OFFSET_KEY: Symbol = symbol_short!("SHROFFST");
 
 // Persistent storage key prefix for LP shares
 pub const LP_SHARES_PREFIX: Symbol = symbol_short!("LPSHRS");
+// Temporary storage key prefix for LP share allowances
+pub const ALLOWANCE_PREFIX: Symbol = symbol_short!("ALLOW");
+// Persistent storage key prefixes for the withdrawal queue
+pub const WITHDRAWAL_PREFIX: Symbol = symbol_short!("WQREQ");
+pub const PENDING_SHARES_PREFIX: Symbol = symbol_short!("WQPEND");
+pub const CLAIMABLE_PREFIX: Symbol = symbol_short!("WQCLAIM");
 
 // TTL constants (~30 days at 5 s/ledger)
 const INSTANCE_BUMP_AMOUNT: u32 = 518_400;
@@ -97,6 +118,25 @@ pub fn set_merchant_fund(env: &Env, merchant_fund: &Address) {
         .set(&MERCHANT_FUND_KEY, merchant_fund);
 }
 
+// --- Interest fee split ---
+
+pub fn get_fee_config(env: &Env) -> FeeConfig {
+    env.storage()
+        .instance()
+        .get(&FEE_CONFIG_KEY)
+        .unwrap_or_else(|| FeeConfig {
+            lp_bps: LP_FEE_BPS,
+            protocol_bps: PROTOCOL_FEE_BPS,
+            merchant_bps: MERCHANT_FEE_BPS,
+            reserve_bps: 0,
+            extra_recipients: Vec::new(env),
+        })
+}
+
+pub fn set_fee_config(env: &Env, config: &FeeConfig) {
+    env.storage().instance().set(&FEE_CONFIG_KEY, config);
+}
+
 // --- Total Shares ---
 
 pub fn get_total_shares(env: &Env) -> i128 {
@@ -133,6 +173,26 @@ pub fn set_locked_liquidity(env: &Env, locked: i128) {
     env.storage().instance().set(&LOCKED_LIQUIDITY_KEY, &locked);
 }
 
+// --- Realised Losses ---
+
+pub fn get_total_losses(env: &Env) -> i128 {
+    env.storage().instance().get(&TOTAL_LOSSES_KEY).unwrap_or(0)
+}
+
+pub fn set_total_losses(env: &Env, losses: i128) {
+    env.storage().instance().set(&TOTAL_LOSSES_KEY, &losses);
+}
+
+// --- Insurance reserve ---
+
+pub fn get_reserve(env: &Env) -> i128 {
+    env.storage().instance().get(&RESERVE_KEY).unwrap_or(0)
+}
+
+pub fn set_reserve(env: &Env, reserve: i128) {
+    env.storage().instance().set(&RESERVE_KEY, &reserve);
+}
+
 // --- LP Shares (persistent per-provider) ---
 
 pub fn get_lp_shares(env: &Env, provider: &Address) -> i128 {
@@ -148,6 +208,168 @@ pub fn set_lp_shares(env: &Env, provider: &Address, shares: i128) {
         .set(&(LP_SHARES_PREFIX, provider.clone()), &shares);
 }
 
+// --- LP share allowances (temporary, live until expiration) ---
+
+pub fn get_allowance(env: &Env, from: &Address, spender: &Address) -> AllowanceValue {
+    let key = (ALLOWANCE_PREFIX, from.clone(), spender.clone());
+    match env.storage().temporary().get::<_, AllowanceValue>(&key) {
+        Some(allowance) if allowance.expiration_ledger >= env.ledger().sequence() => allowance,
+        _ => AllowanceValue {
+            amount: 0,
+            expiration_ledger: 0,
+        },
+    }
+}
+
+pub fn set_allowance(env: &Env, from: &Address, spender: &Address, allowance: &AllowanceValue) {
+    let key = (ALLOWANCE_PREFIX, from.clone(), spender.clone());
+    env.storage().temporary().set(&key, allowance);
+    if allowance.amount > 0 {
+        let live_for = allowance
+            .expiration_ledger
+            .saturating_sub(env.ledger().sequence());
+        env.storage()
+            .temporary()
+            .extend_ttl(&key, live_for, live_for);
+    }
+}
+
+// --- LP share metadata ---
+
+pub fn get_share_name(env: &Env) -> String {
+    env.storage()
+        .instance()
+        .get(&SHARE_NAME_KEY)
+        .unwrap_or_else(|| String::from_str(env, SHARE_NAME))
+}
+
+pub fn get_share_symbol(env: &Env) -> String {
+    env.storage()
+        .instance()
+        .get(&SHARE_SYMBOL_KEY)
+        .unwrap_or_else(|| String::from_str(env, SHARE_SYMBOL))
+}
+
+pub fn set_share_metadata(env: &Env, name: &String, symbol: &String) {
+    env.storage().instance().set(&SHARE_NAME_KEY, name);
+    env.storage().instance().set(&SHARE_SYMBOL_KEY, symbol);
+}
+
+/// Pools initialised before shares carried a decimals offset have none
+/// stored: their shares were issued 1:1 with the token.
+pub fn get_share_decimals_offset(env: &Env) -> u32 {
+    env.storage().instance().get(&SHARE_OFFSET_KEY).unwrap_or(0)
+}
+
+pub fn set_share_decimals_offset(env: &Env, offset: u32) {
+    env.storage().instance().set(&SHARE_OFFSET_KEY, &offset);
+}
+
+// --- Withdrawal queue ---
+//
+// Requests are numbered from 0 in arrival order. `head` is the oldest
+// unfilled request and `tail` the id the next request gets, so the queue
+// holds ids `head..tail`.
+
+pub fn get_queue_head(env: &Env) -> u64 {
+    env.storage().instance().get(&QUEUE_HEAD_KEY).unwrap_or(0)
+}
+
+pub fn set_queue_head(env: &Env, head: u64) {
+    env.storage().instance().set(&QUEUE_HEAD_KEY, &head);
+}
+
+pub fn get_queue_tail(env: &Env) -> u64 {
+    env.storage().instance().get(&QUEUE_TAIL_KEY).unwrap_or(0)
+}
+
+pub fn set_queue_tail(env: &Env, tail: u64) {
+    env.storage().instance().set(&QUEUE_TAIL_KEY, &tail);
+}
+
+pub fn get_queued_shares(env: &Env) -> i128 {
+    env.storage()
+        .instance()
+        .get(&QUEUED_SHARES_KEY)
+        .unwrap_or(0)
+}
+
+pub fn set_queued_shares(env: &Env, shares: i128) {
+    env.storage().instance().set(&QUEUED_SHARES_KEY, &shares);
+}
+
+pub fn get_withdrawal_request(env: &Env, request_id: u64) -> Option<WithdrawalRequest> {
+    env.storage()
+        .persistent()
+        .get(&(WITHDRAWAL_PREFIX, request_id))
+}
+
+pub fn set_withdrawal_request(env: &Env, request_id: u64, request: &WithdrawalRequest) {
+    let key = (WITHDRAWAL_PREFIX, request_id);
+    env.storage().persistent().set(&key, request);
+    env.storage().persistent().extend_ttl(
+        &key,
+        PERSISTENT_LIFETIME_THRESHOLD,
+        PERSISTENT_BUMP_AMOUNT,
+    );
+}
+
+pub fn remove_withdrawal_request(env: &Env, request_id: u64) {
+    env.storage()
+        .persistent()
+        .remove(&(WITHDRAWAL_PREFIX, request_id));
+}
+
+pub fn get_pending_shares(env: &Env, provider: &Address) -> i128 {
+    env.storage()
+        .persistent()
+        .get(&(PENDING_SHARES_PREFIX, provider.clone()))
+        .unwrap_or(0)
+}
+
+pub fn set_pending_shares(env: &Env, provider: &Address, shares: i128) {
+    let key = (PENDING_SHARES_PREFIX, provider.clone());
+    if shares == 0 {
+        env.storage().persistent().remove(&key);
+    } else {
+        env.storage().persistent().set(&key, &shares);
+    }
+}
+
+// Tokens of filled requests waiting for their provider to claim them.
+
+pub fn get_claimable(env: &Env, provider: &Address) -> i128 {
+    env.storage()
+        .persistent()
+        .get(&(CLAIMABLE_PREFIX, provider.clone()))
+        .unwrap_or(0)
+}
+
+pub fn set_claimable(env: &Env, provider: &Address, amount: i128) {
+    let key = (CLAIMABLE_PREFIX, provider.clone());
+    if amount == 0 {
+        env.storage().persistent().remove(&key);
+    } else {
+        env.storage().persistent().set(&key, &amount);
+        env.storage().persistent().extend_ttl(
+            &key,
+            PERSISTENT_LIFETIME_THRESHOLD,
+            PERSISTENT_BUMP_AMOUNT,
+        );
+    }
+}
+
+pub fn get_claimable_total(env: &Env) -> i128 {
+    env.storage()
+        .instance()
+        .get(&CLAIMABLE_TOTAL_KEY)
+        .unwrap_or(0)
+}
+
+pub fn set_claimable_total(env: &Env, total: i128) {
+    env.storage().instance().set(&CLAIMABLE_TOTAL_KEY, &total);
+}
+
 pub fn is_reentrancy_locked(env: &Env) -> bool {
     env.storage()
         .instance()
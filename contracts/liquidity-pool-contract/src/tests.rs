@@ -1,9 +1,9 @@
-use crate::{LiquidityPoolContract, LiquidityPoolContractClient};
+use crate::{FeeConfig, FeeRecipient, LiquidityPoolContract, LiquidityPoolContractClient};
 use soroban_sdk::{
-    symbol_short,
-    testutils::{Address as _, Events},
+    contract, contractimpl, symbol_short,
+    testutils::{Address as _, Events, IssuerFlags, Ledger},
     token::{Client as TokenClient, StellarAssetClient},
-    Address, Env, IntoVal, Symbol, Val, Vec,
+    vec, Address, Env, IntoVal, String, Symbol, Val, Vec,
 };
 
 // ─── helpers ──────────────────────────────────────────────────────────────────
@@ -26,6 +26,10 @@ impl TestEnv {
         let token_admin = Address::generate(&env);
         let token_contract_id = env.register_stellar_asset_contract_v2(token_admin.clone());
         let token_address = token_contract_id.address();
+        // Revocable, so tests can freeze an account's balance
+        token_contract_id
+            .issuer()
+            .set_flag(IssuerFlags::RevocableFlag);
 
         let token_sac = StellarAssetClient::new(&env, &token_address);
         let contract_id = env.register(LiquidityPoolContract, ());
@@ -99,15 +103,15 @@ fn test_first_deposit_one_to_one_ratio() {
     let provider = Address::generate(&t.env);
     t.mint(&provider, 1_000);
 
-    let shares = t.client().deposit(&provider, &1_000);
+    let shares = t.client().deposit(&provider, &1_000, &0);
 
-    // First deposit → shares == amount
-    assert_eq!(shares, 1_000);
-    assert_eq!(t.client().get_lp_shares(&provider), 1_000);
+    // First deposit → VIRTUAL_SHARES shares per token
+    assert_eq!(shares, 1_000_000);
+    assert_eq!(t.client().get_lp_shares(&provider), 1_000_000);
 
     let stats = t.client().get_pool_stats();
     assert_eq!(stats.total_liquidity, 1_000);
-    assert_eq!(stats.total_shares, 1_000);
+    assert_eq!(stats.total_shares, 1_000_000);
     assert_eq!(stats.locked_liquidity, 0);
     assert_eq!(stats.available_liquidity, 1_000);
 }
@@ -122,15 +126,15 @@ fn test_subsequent_deposit_proportional_shares() {
     t.mint(&provider_b, 1_000);
 
     // First deposit
-    t.client().deposit(&provider_a, &1_000);
+    t.client().deposit(&provider_a, &1_000, &0);
 
     // Second deposit: same amount → same shares (pool value unchanged)
-    let shares_b = t.client().deposit(&provider_b, &1_000);
-    assert_eq!(shares_b, 1_000);
+    let shares_b = t.client().deposit(&provider_b, &1_000, &0);
+    assert_eq!(shares_b, 1_000_000);
 
     let stats = t.client().get_pool_stats();
     assert_eq!(stats.total_liquidity, 2_000);
-    assert_eq!(stats.total_shares, 2_000);
+    assert_eq!(stats.total_shares, 2_000_000);
 }
 
 #[test]
@@ -144,11 +148,11 @@ fn test_deposit_after_interest_increases_share_value() {
     t.mint(&provider_a, 1_000);
     t.mint(&provider_b, 1_000);
 
-    // First deposit: 1000 tokens → 1000 shares
-    t.client().deposit(&provider_a, &1_000);
+    // First deposit: 1000 tokens → 1_000_000 shares
+    t.client().deposit(&provider_a, &1_000, &0);
 
     // Simulate interest: distribute 100 tokens of interest.
-    // 85 stays in pool → total_liquidity becomes 1085, total_shares stays 1000.
+    // 85 stays in pool → total_liquidity becomes 1085, total_shares stays 1_000_000.
     // share_price = 1085/1000 = 1.085
     // The test helper calls distribute_interest directly:
     // We inject interest by sending tokens to the pool and calling receive_repayment
@@ -157,16 +161,16 @@ fn test_deposit_after_interest_increases_share_value() {
     t.client().receive_repayment(&t.creditline, &0, &100);
 
     // Now total_liquidity includes the LP portion (85) of interest.
-    // Pool: total_liquidity = 1000 + 85 = 1085, total_shares = 1000
-    // Second deposit of 1000 tokens: shares = 1000 * 1000 / 1085 ≈ 921
-    let shares_b = t.client().deposit(&provider_b, &1_000);
+    // Pool: total_liquidity = 1000 + 85 = 1085, total_shares = 1_000_000
+    // Second deposit of 1000 tokens: shares = 1000 * 1_001_000 / 1086 ≈ 921_731
+    let shares_b = t.client().deposit(&provider_b, &1_000, &0);
     assert!(
-        shares_b < 1_000,
-        "Shares must be < 1000 since pool value grew"
+        shares_b < 1_000_000,
+        "Shares must be < 1_000_000 since pool value grew"
     );
 
-    // provider_a's shares are still 1000 but worth more
-    assert_eq!(t.client().get_lp_shares(&provider_a), 1_000);
+    // provider_a's shares are unchanged but worth more
+    assert_eq!(t.client().get_lp_shares(&provider_a), 1_000_000);
 }
 
 #[test]
@@ -174,7 +178,7 @@ fn test_deposit_after_interest_increases_share_value() {
 fn test_deposit_zero_amount_fails() {
     let t = TestEnv::setup();
     let provider = Address::generate(&t.env);
-    t.client().deposit(&provider, &0);
+    t.client().deposit(&provider, &0, &0);
 }
 
 #[test]
@@ -182,7 +186,108 @@ fn test_deposit_zero_amount_fails() {
 fn test_deposit_negative_amount_fails() {
     let t = TestEnv::setup();
     let provider = Address::generate(&t.env);
-    t.client().deposit(&provider, &-500);
+    t.client().deposit(&provider, &-500, &0);
+}
+
+// ─── first-depositor inflation ────────────────────────────────────────────────
+
+impl TestEnv {
+    /// The classic attack: make the smallest allowed first deposit, withdraw
+    /// all but one share, then donate `donation` through a recovery payment
+    /// (which anyone may make on a defaulted loan) so the last share is worth
+    /// the whole pool.
+    fn inflate_first_deposit(&self, attacker: &Address, donation: i128) {
+        self.mint(attacker, 1_000);
+        self.client().deposit(attacker, &1_000, &0);
+        self.client().withdraw(attacker, &999_999);
+        self.client().receive_recovery(&self.creditline, &donation);
+    }
+}
+
+/// Shares the pool issued before the virtual offsets: 1:1 into an empty
+/// pool, then `amount * total_shares / total_liquidity`.
+fn legacy_shares_for(amount: i128, total_shares: i128, total_liquidity: i128) -> i128 {
+    if total_shares == 0 {
+        amount
+    } else {
+        amount * total_shares / total_liquidity
+    }
+}
+
+#[test]
+fn test_inflation_attack_profits_under_legacy_share_formula() {
+    // Replay the attack against the old formula
+    let (mut total_shares, mut total_liquidity) = (0, 0);
+    let attacker_shares = legacy_shares_for(1_000, total_shares, total_liquidity);
+    total_shares += attacker_shares - 999;
+    total_liquidity += 1_000 - 999;
+    total_liquidity += 1_000; // donation
+    let attacker_cost = 1_000 - 999 + 1_000;
+
+    let victim_shares = legacy_shares_for(2_000, total_shares, total_liquidity);
+    assert_eq!(victim_shares, 1);
+    total_shares += victim_shares;
+    total_liquidity += 2_000;
+
+    // Each of the two shares now redeems for half of the 3_001 pooled
+    let victim_out = victim_shares * total_liquidity / total_shares;
+    let attacker_out = total_liquidity - victim_out;
+    assert_eq!((victim_out, attacker_out), (1_500, 1_501));
+    assert!(attacker_out - attacker_cost >= 500, "attacker profits");
+}
+
+#[test]
+fn test_inflation_attack_is_unprofitable() {
+    let t = TestEnv::setup();
+    let attacker = Address::generate(&t.env);
+    let victim = Address::generate(&t.env);
+    t.inflate_first_deposit(&attacker, 1_000);
+    let stats = t.client().get_pool_stats();
+    assert_eq!((stats.total_shares, stats.total_liquidity), (1, 1_001));
+
+    // The same deposit that got 1 share above is barely rounded: the
+    // virtual shares keep almost all of the donation.
+    t.mint(&victim, 2_000);
+    let victim_shares = t.client().deposit(&victim, &2_000, &0);
+    assert_eq!(victim_shares, 1_998);
+
+    let victim_out = t.client().withdraw(&victim, &victim_shares);
+    assert!(2_000 - victim_out <= 1, "victim loses only rounding dust");
+    let attacker_out = t.client().withdraw(&attacker, &1);
+    assert!(attacker_out <= 2, "attacker loses the donation");
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #17)")] // SlippageExceeded
+fn test_deposit_min_shares_out_rejects_inflated_price() {
+    let t = TestEnv::setup();
+    let attacker = Address::generate(&t.env);
+    let victim = Address::generate(&t.env);
+    t.inflate_first_deposit(&attacker, 1_000_000);
+
+    // The victim expects roughly 1_000 shares per token at the quoted price
+    t.mint(&victim, 1_999);
+    t.client().deposit(&victim, &1_999, &1_990_000);
+}
+
+#[test]
+fn test_deposit_min_shares_out_met() {
+    let t = TestEnv::setup();
+    let provider = Address::generate(&t.env);
+    t.mint(&provider, 2_000);
+
+    assert_eq!(t.client().deposit(&provider, &1_000, &1_000_000), 1_000_000);
+    assert_eq!(t.client().deposit(&provider, &1_000, &1_000_000), 1_000_000);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #18)")] // FirstDepositTooSmall
+fn test_first_deposit_below_minimum_fails() {
+    let t = TestEnv::setup();
+    let provider = Address::generate(&t.env);
+    t.mint(&provider, 999);
+
+    t.client().deposit(&provider, &999, &0);
 }
 
 // ─── withdraw ─────────────────────────────────────────────────────────────────
@@ -193,9 +298,9 @@ fn test_full_withdrawal() {
     let provider = Address::generate(&t.env);
     t.mint(&provider, 1_000);
 
-    t.client().deposit(&provider, &1_000);
+    t.client().deposit(&provider, &1_000, &0);
 
-    let amount_returned = t.client().withdraw(&provider, &1_000);
+    let amount_returned = t.client().withdraw(&provider, &1_000_000);
     assert_eq!(amount_returned, 1_000);
     assert_eq!(t.client().get_lp_shares(&provider), 0);
 
@@ -210,15 +315,15 @@ fn test_partial_withdrawal() {
     let provider = Address::generate(&t.env);
     t.mint(&provider, 1_000);
 
-    t.client().deposit(&provider, &1_000);
+    t.client().deposit(&provider, &1_000, &0);
 
-    let amount_returned = t.client().withdraw(&provider, &400);
+    let amount_returned = t.client().withdraw(&provider, &400_000);
     assert_eq!(amount_returned, 400);
-    assert_eq!(t.client().get_lp_shares(&provider), 600);
+    assert_eq!(t.client().get_lp_shares(&provider), 600_000);
 
     let stats = t.client().get_pool_stats();
     assert_eq!(stats.total_liquidity, 600);
-    assert_eq!(stats.total_shares, 600);
+    assert_eq!(stats.total_shares, 600_000);
 }
 
 #[test]
@@ -228,16 +333,17 @@ fn test_withdrawal_reflects_share_appreciation() {
     let provider = Address::generate(&t.env);
     t.mint(&provider, 1_000);
 
-    t.client().deposit(&provider, &1_000);
+    t.client().deposit(&provider, &1_000, &0);
 
     // Distribute 100 interest (85 stays in pool)
     t.mint(&t.creditline, 100);
     t.client().receive_repayment(&t.creditline, &0, &100);
 
-    // Total_liquidity = 1085, total_shares = 1000
-    // Withdraw all 1000 shares → should receive 1085 tokens
-    let amount_returned = t.client().withdraw(&provider, &1_000);
-    assert_eq!(amount_returned, 1_085);
+    // Total_liquidity = 1085, total_shares = 1_000_000
+    // Withdraw all shares → 1_000_000 * 1086 / 1_001_000 = 1084 tokens
+    // (the virtual shares keep their sliver of the interest)
+    let amount_returned = t.client().withdraw(&provider, &1_000_000);
+    assert_eq!(amount_returned, 1_084);
 }
 
 #[test]
@@ -246,8 +352,8 @@ fn test_withdraw_more_shares_than_owned_fails() {
     let t = TestEnv::setup();
     let provider = Address::generate(&t.env);
     t.mint(&provider, 1_000);
-    t.client().deposit(&provider, &1_000);
-    t.client().withdraw(&provider, &1_001);
+    t.client().deposit(&provider, &1_000, &0);
+    t.client().withdraw(&provider, &1_000_001);
 }
 
 #[test]
@@ -257,13 +363,13 @@ fn test_withdraw_when_liquidity_locked_fails() {
     let provider = Address::generate(&t.env);
     let merchant = Address::generate(&t.env);
     t.mint(&provider, 1_000);
-    t.client().deposit(&provider, &1_000);
+    t.client().deposit(&provider, &1_000, &0);
 
     // Lock all liquidity in a loan
     t.client().fund_loan(&t.creditline, &merchant, &1_000);
 
     // Try to withdraw → all liquidity is locked
-    t.client().withdraw(&provider, &1_000);
+    t.client().withdraw(&provider, &1_000_000);
 }
 
 #[test]
@@ -282,7 +388,7 @@ fn test_fund_loan_increases_locked_liquidity() {
     let provider = Address::generate(&t.env);
     let merchant = Address::generate(&t.env);
     t.mint(&provider, 1_000);
-    t.client().deposit(&provider, &1_000);
+    t.client().deposit(&provider, &1_000, &0);
 
     t.client().fund_loan(&t.creditline, &merchant, &400);
 
@@ -299,7 +405,7 @@ fn test_fund_loan_exceeds_available_fails() {
     let provider = Address::generate(&t.env);
     let merchant = Address::generate(&t.env);
     t.mint(&provider, 1_000);
-    t.client().deposit(&provider, &1_000);
+    t.client().deposit(&provider, &1_000, &0);
 
     // Try to fund more than available
     t.client().fund_loan(&t.creditline, &merchant, &1_001);
@@ -322,7 +428,7 @@ fn test_receive_repayment_decreases_locked_and_distributes_interest() {
     let provider = Address::generate(&t.env);
     let merchant = Address::generate(&t.env);
     t.mint(&provider, 1_000);
-    t.client().deposit(&provider, &1_000);
+    t.client().deposit(&provider, &1_000, &0);
 
     // Fund a 400-token loan
     t.client().fund_loan(&t.creditline, &merchant, &400);
@@ -345,7 +451,7 @@ fn test_receive_repayment_treasury_receives_protocol_fee() {
     let t = TestEnv::setup();
     let provider = Address::generate(&t.env);
     t.mint(&provider, 1_000);
-    t.client().deposit(&provider, &1_000);
+    t.client().deposit(&provider, &1_000, &0);
 
     // Send 100 interest
     t.mint(&t.creditline, 100);
@@ -361,7 +467,7 @@ fn test_receive_repayment_merchant_fund_receives_fee() {
     let t = TestEnv::setup();
     let provider = Address::generate(&t.env);
     t.mint(&provider, 1_000);
-    t.client().deposit(&provider, &1_000);
+    t.client().deposit(&provider, &1_000, &0);
 
     // Send 100 interest
     t.mint(&t.creditline, 100);
@@ -381,7 +487,7 @@ fn test_double_counting_does_not_compound_across_multiple_loan_cycles() {
     let provider = Address::generate(&t.env);
     let merchant = Address::generate(&t.env);
     t.mint(&provider, 1_000);
-    t.client().deposit(&provider, &1_000);
+    t.client().deposit(&provider, &1_000, &0);
 
     // Cycle 1
     t.client().fund_loan(&t.creditline, &merchant, &600);
@@ -405,7 +511,7 @@ fn test_double_counting_does_not_compound_across_multiple_loan_cycles() {
     assert_eq!(t.client().get_pool_stats().total_liquidity, 1_000);
 
     // Provider must be able to withdraw their full original deposit
-    let returned = t.client().withdraw(&provider, &1_000);
+    let returned = t.client().withdraw(&provider, &1_000_000);
     assert_eq!(returned, 1_000);
 }
 
@@ -417,7 +523,7 @@ fn test_distribute_interest_fee_split_accuracy() {
     let t = TestEnv::setup();
     let provider = Address::generate(&t.env);
     t.mint(&provider, 10_000);
-    t.client().deposit(&provider, &10_000);
+    t.client().deposit(&provider, &10_000, &0);
 
     t.mint(&t.creditline, 1_000);
     t.client().receive_repayment(&t.creditline, &0, &1_000);
@@ -442,7 +548,7 @@ fn test_distribute_interest_share_value_appreciation() {
     let t = TestEnv::setup();
     let provider = Address::generate(&t.env);
     t.mint(&provider, 1_000);
-    t.client().deposit(&provider, &1_000);
+    t.client().deposit(&provider, &1_000, &0);
 
     let stats_before = t.client().get_pool_stats();
     assert_eq!(stats_before.share_price, 10_000); // $1.00 in bps
@@ -468,24 +574,24 @@ fn test_multiple_lp_proportional_distribution() {
     t.mint(&provider_a, 1_000);
     t.mint(&provider_b, 1_000);
 
-    t.client().deposit(&provider_a, &1_000);
-    t.client().deposit(&provider_b, &1_000);
+    t.client().deposit(&provider_a, &1_000, &0);
+    t.client().deposit(&provider_b, &1_000, &0);
 
     // 200 interest distributed (100 per LP proportionally)
     t.mint(&t.creditline, 200);
     t.client().receive_repayment(&t.creditline, &0, &200);
 
     // LP amount = 85% of 200 = 170 → added to pool
-    // total_liquidity = 2000 + 170 = 2170, total_shares = 2000
+    // total_liquidity = 2000 + 170 = 2170, total_shares = 2_000_000
     let stats = t.client().get_pool_stats();
     assert_eq!(stats.total_liquidity, 2_170);
 
-    // Both LPs hold 1000 shares out of 2000 → each owns 50% of pool
-    // Withdrawal value per LP = 1000 * 2170 / 2000 = 1085
-    let val_a = t.client().calculate_withdrawal(&1_000);
-    let val_b = t.client().calculate_withdrawal(&1_000);
-    assert_eq!(val_a, 1_085);
-    assert_eq!(val_b, 1_085);
+    // Both LPs hold 1_000_000 shares out of 2_000_000 → each owns 50% of pool
+    // Withdrawal value per LP = 1_000_000 * 2171 / 2_001_000 = 1084 (virtual offsets)
+    let val_a = t.client().calculate_withdrawal(&1_000_000);
+    let val_b = t.client().calculate_withdrawal(&1_000_000);
+    assert_eq!(val_a, 1_084);
+    assert_eq!(val_b, 1_084);
 }
 
 #[test]
@@ -494,7 +600,7 @@ fn test_interest_calculation_accuracy_small_amount() {
     let t = TestEnv::setup();
     let provider = Address::generate(&t.env);
     t.mint(&provider, 1_000);
-    t.client().deposit(&provider, &1_000);
+    t.client().deposit(&provider, &1_000, &0);
 
     t.mint(&t.creditline, 100);
     t.client().receive_repayment(&t.creditline, &0, &100);
@@ -515,7 +621,7 @@ fn test_interest_rounding_remainder_goes_to_lp() {
     let t = TestEnv::setup();
     let provider = Address::generate(&t.env);
     t.mint(&provider, 10_000);
-    t.client().deposit(&provider, &10_000);
+    t.client().deposit(&provider, &10_000, &0);
 
     t.mint(&t.creditline, 101);
     t.client().receive_repayment(&t.creditline, &0, &101);
@@ -532,7 +638,7 @@ fn test_receive_guarantee_reduces_locked_and_recovers_liquidity() {
     let provider = Address::generate(&t.env);
     let merchant = Address::generate(&t.env);
     t.mint(&provider, 1_000);
-    t.client().deposit(&provider, &1_000);
+    t.client().deposit(&provider, &1_000, &0);
 
     // Fund a 500-token loan
     t.client().fund_loan(&t.creditline, &merchant, &500);
@@ -544,11 +650,727 @@ fn test_receive_guarantee_reduces_locked_and_recovers_liquidity() {
     let stats = t.client().get_pool_stats();
     // locked was 500, reduced by 100 → 400
     assert_eq!(stats.locked_liquidity, 400);
-    // total_liquidity was 1000, recovered 100 → 1100... no wait:
     // fund_loan doesn't change total_liquidity, it changes locked.
     // After fund_loan: total=1000, locked=500, available=500.
-    // receive_guarantee adds 100 to total, reduces locked by 100.
-    assert_eq!(stats.total_liquidity, 1_100);
+    // receive_guarantee turns 100 of locked capital back into free tokens.
+    assert_eq!(stats.total_liquidity, 1_000);
+    assert_eq!(stats.available_liquidity, 600);
+    assert_eq!(t.token().balance(&t.contract_id), 600);
+}
+
+// ─── receive_recovery ─────────────────────────────────────────────────────────
+
+#[test]
+fn test_receive_recovery_adds_liquidity_without_releasing_locked() {
+    let t = TestEnv::setup();
+    let provider = Address::generate(&t.env);
+    let merchant = Address::generate(&t.env);
+    t.mint(&provider, 1_000);
+    t.client().deposit(&provider, &1_000, &0);
+    t.client().fund_loan(&t.creditline, &merchant, &500);
+
+    t.client().receive_recovery(&t.creditline, &300);
+
+    // The locked 500 belongs to a loan still running
+    let stats = t.client().get_pool_stats();
+    assert_eq!(stats.locked_liquidity, 500);
+    assert_eq!(stats.available_liquidity, 800);
+    assert_eq!(stats.total_liquidity, 1_300);
+    assert_eq!(t.token().balance(&t.contract_id), 800);
+}
+
+#[test]
+fn test_receive_recovery_accepts_amount_above_locked() {
+    let t = TestEnv::setup();
+    let provider = Address::generate(&t.env);
+    let merchant = Address::generate(&t.env);
+    t.mint(&provider, 1_000);
+    t.client().deposit(&provider, &1_000, &0);
+    t.client().fund_loan(&t.creditline, &merchant, &100);
+
+    t.client().receive_recovery(&t.creditline, &250);
+
+    let stats = t.client().get_pool_stats();
+    assert_eq!(stats.locked_liquidity, 100);
+    assert_eq!(stats.total_liquidity, 1_250);
+    assert_eq!(t.token().balance(&t.contract_id), 1_150);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #9)")]
+fn test_receive_recovery_unauthorized_caller_fails() {
+    let t = TestEnv::setup();
+    let intruder = Address::generate(&t.env);
+    t.mint(&intruder, 100);
+    t.client().receive_recovery(&intruder, &100);
+}
+
+// ─── write_off ────────────────────────────────────────────────────────────────
+
+#[test]
+fn test_write_off_removes_loss_from_locked_and_total() {
+    let t = TestEnv::setup();
+    let provider = Address::generate(&t.env);
+    let merchant = Address::generate(&t.env);
+    t.mint(&provider, 1_000);
+    t.client().deposit(&provider, &1_000, &0);
+    t.client().fund_loan(&t.creditline, &merchant, &500);
+
+    t.client().write_off(&t.creditline, &300);
+
+    let stats = t.client().get_pool_stats();
+    assert_eq!(stats.locked_liquidity, 200);
+    assert_eq!(stats.total_liquidity, 700);
+    assert_eq!(stats.available_liquidity, 500);
+    assert_eq!(stats.share_price, 7_000);
+    assert_eq!(stats.total_losses, 300);
+    assert_eq!(t.token().balance(&t.contract_id), 500);
+}
+
+#[test]
+fn test_write_off_accumulates_losses() {
+    let t = TestEnv::setup();
+    let provider = Address::generate(&t.env);
+    let merchant = Address::generate(&t.env);
+    t.mint(&provider, 1_000);
+    t.client().deposit(&provider, &1_000, &0);
+    t.client().fund_loan(&t.creditline, &merchant, &300);
+
+    t.client().write_off(&t.creditline, &150);
+    t.client().write_off(&t.creditline, &100);
+
+    let stats = t.client().get_pool_stats();
+    assert_eq!(stats.locked_liquidity, 50);
+    assert_eq!(stats.total_liquidity, 750);
+    assert_eq!(stats.total_losses, 250);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #21)")] // WriteOffExceedsLocked
+fn test_write_off_more_than_locked_fails() {
+    let t = TestEnv::setup();
+    let provider = Address::generate(&t.env);
+    let merchant = Address::generate(&t.env);
+    t.mint(&provider, 1_000);
+    t.client().deposit(&provider, &1_000, &0);
+    t.client().fund_loan(&t.creditline, &merchant, &200);
+
+    t.client().write_off(&t.creditline, &201);
+}
+
+#[test]
+fn test_write_off_emits_loss_event() {
+    let t = TestEnv::setup();
+    let provider = Address::generate(&t.env);
+    let merchant = Address::generate(&t.env);
+    t.mint(&provider, 1_000);
+    t.client().deposit(&provider, &1_000, &0);
+    t.client().fund_loan(&t.creditline, &merchant, &500);
+
+    t.client().write_off(&t.creditline, &300);
+
+    let events: Vec<(Address, Vec<Val>, Val)> = t.env.events().all();
+    let (_, topics, data) = events.last().unwrap();
+    let event_type: Symbol = topics.get(0).unwrap().into_val(&t.env);
+    let (amount, total_losses): (i128, i128) = data.into_val(&t.env);
+    assert_eq!(event_type, symbol_short!("LQLOSS"));
+    assert_eq!(amount, 300);
+    assert_eq!(total_losses, 300);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #9)")]
+fn test_write_off_unauthorized_caller_fails() {
+    let t = TestEnv::setup();
+    let intruder = Address::generate(&t.env);
+    t.client().write_off(&intruder, &100);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #4)")]
+fn test_write_off_zero_amount_fails() {
+    let t = TestEnv::setup();
+    t.client().write_off(&t.creditline, &0);
+}
+
+// ─── sell_receivable ──────────────────────────────────────────────────────────
+
+/// Stand-in creditline that reports 400 of pool capital in every loan it assigns.
+#[contract]
+struct MockReceivables;
+
+#[contractimpl]
+impl MockReceivables {
+    pub fn transfer_receivable(_env: Env, _loan_id: u64, _to: Address) -> i128 {
+        400
+    }
+}
+
+impl TestEnv {
+    /// Fund 1_000 of liquidity and lock 400 in a loan made through a creditline
+    /// that supports receivable transfers; returns that creditline.
+    fn setup_receivable(&self) -> Address {
+        let creditline = self.env.register(MockReceivables, ());
+        self.client().set_creditline(&self.admin, &creditline);
+        let provider = Address::generate(&self.env);
+        self.mint(&provider, 1_000);
+        self.client().deposit(&provider, &1_000, &0);
+        let merchant = Address::generate(&self.env);
+        self.client().fund_loan(&creditline, &merchant, &400);
+        creditline
+    }
+}
+
+#[test]
+fn test_sell_receivable_at_par_releases_locked_capital() {
+    let t = TestEnv::setup();
+    t.setup_receivable();
+    let buyer = Address::generate(&t.env);
+    t.mint(&buyer, 400);
+
+    t.client().sell_receivable(&t.admin, &1, &buyer, &400);
+
+    let stats = t.client().get_pool_stats();
+    assert_eq!(stats.locked_liquidity, 0);
+    assert_eq!(stats.total_liquidity, 1_000);
+    assert_eq!(stats.available_liquidity, 1_000);
+    assert_eq!(stats.total_losses, 0);
+    assert_eq!(t.token().balance(&t.contract_id), 1_000);
+    assert_eq!(t.token().balance(&buyer), 0);
+}
+
+#[test]
+fn test_sell_receivable_at_discount_books_loss() {
+    let t = TestEnv::setup();
+    t.setup_receivable();
+    let buyer = Address::generate(&t.env);
+    t.mint(&buyer, 350);
+
+    t.client().sell_receivable(&t.admin, &1, &buyer, &350);
+
+    let stats = t.client().get_pool_stats();
+    assert_eq!(stats.locked_liquidity, 0);
+    assert_eq!(stats.total_liquidity, 950);
+    assert_eq!(stats.total_losses, 50);
+    assert_eq!(t.token().balance(&t.contract_id), 950);
+}
+
+#[test]
+fn test_sell_receivable_emits_event() {
+    let t = TestEnv::setup();
+    t.setup_receivable();
+    let buyer = Address::generate(&t.env);
+    t.mint(&buyer, 420);
+
+    t.client().sell_receivable(&t.admin, &7, &buyer, &420);
+
+    let events: Vec<(Address, Vec<Val>, Val)> = t.env.events().all();
+    let (_, topics, data) = events.last().unwrap();
+    let event_type: Symbol = topics.get(0).unwrap().into_val(&t.env);
+    let loan_id: u64 = topics.get(1).unwrap().into_val(&t.env);
+    let (event_buyer, released, price): (Address, i128, i128) = data.into_val(&t.env);
+    assert_eq!(event_type, symbol_short!("LQRCVSLD"));
+    assert_eq!(loan_id, 7);
+    assert_eq!(event_buyer, buyer);
+    assert_eq!(released, 400);
+    assert_eq!(price, 420);
+    assert_eq!(t.client().get_pool_stats().total_liquidity, 1_020);
+}
+
+#[test]
+fn test_sell_receivable_at_discount_draws_on_reserve_first() {
+    let t = TestEnv::setup();
+    t.top_up_reserve(30);
+    t.setup_receivable();
+    let buyer = Address::generate(&t.env);
+    t.mint(&buyer, 350);
+
+    t.client().sell_receivable(&t.admin, &1, &buyer, &350);
+
+    let stats = t.client().get_pool_stats();
+    assert_eq!(stats.reserve_balance, 0);
+    assert_eq!(stats.total_liquidity, 980);
+    assert_eq!(stats.total_losses, 20);
+
+    let events: Vec<(Address, Vec<Val>, Val)> = t.env.events().all();
+    let loss = events.iter().find(|(_, topics, _)| {
+        let event_type: Symbol = topics.get(0).unwrap().into_val(&t.env);
+        event_type == symbol_short!("LQLOSS")
+    });
+    let (_, _, data) = loss.expect("loss event");
+    let (lost, total_losses): (i128, i128) = data.into_val(&t.env);
+    assert_eq!((lost, total_losses), (20, 20));
+}
+
+#[test]
+fn test_sell_receivable_fills_withdrawal_queue() {
+    let t = TestEnv::setup();
+    let creditline = t.env.register(MockReceivables, ());
+    t.client().set_creditline(&t.admin, &creditline);
+    let provider = Address::generate(&t.env);
+    t.mint(&provider, 1_000);
+    t.client().deposit(&provider, &1_000, &0);
+    let merchant = Address::generate(&t.env);
+    t.client().fund_loan(&creditline, &merchant, &1_000);
+    t.client().request_withdrawal(&provider, &400_000);
+    assert_eq!(t.client().get_withdrawal_queue_length(), 1);
+    let buyer = Address::generate(&t.env);
+    t.mint(&buyer, 400);
+
+    t.client().sell_receivable(&t.admin, &1, &buyer, &400);
+
+    assert_eq!(t.client().get_claimable_withdrawal(&provider), 400);
+    assert_eq!(t.client().get_withdrawal_queue_length(), 0);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #1)")]
+fn test_sell_receivable_non_admin_fails() {
+    let t = TestEnv::setup();
+    t.setup_receivable();
+    let buyer = Address::generate(&t.env);
+
+    t.client().sell_receivable(&buyer, &1, &buyer, &400);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #4)")]
+fn test_sell_receivable_zero_price_fails() {
+    let t = TestEnv::setup();
+    t.setup_receivable();
+    let buyer = Address::generate(&t.env);
+
+    t.client().sell_receivable(&t.admin, &1, &buyer, &0);
+}
+
+// ─── withdrawal queue ─────────────────────────────────────────────────────────
+
+impl TestEnv {
+    /// Deposit 1_000 from a new provider and lock `locked` of it in a loan.
+    fn setup_locked(&self, locked: i128) -> Address {
+        let provider = Address::generate(&self.env);
+        self.mint(&provider, 1_000);
+        self.client().deposit(&provider, &1_000, &0);
+        let merchant = Address::generate(&self.env);
+        let client = self.client();
+        client.fund_loan(&self.creditline, &merchant, &locked);
+        provider
+    }
+}
+
+#[test]
+fn test_request_withdrawal_escrows_shares() {
+    let t = TestEnv::setup();
+    let provider = t.setup_locked(800);
+
+    let request_id = t.client().request_withdrawal(&provider, &500_000);
+
+    assert_eq!(request_id, 0);
+    assert_eq!(t.client().get_lp_shares(&provider), 500_000);
+    assert_eq!(t.client().get_pending_withdrawal(&provider), 500_000);
+    assert_eq!(t.client().get_pending_withdrawal_amount(&provider), 500);
+    assert_eq!(t.client().get_queued_shares(), 500_000);
+    assert_eq!(t.client().get_withdrawal_queue_length(), 1);
+    assert_eq!(t.client().get_withdrawal_queue_position(&request_id), 0);
+    // Escrowed shares still count towards the pool until filled
+    assert_eq!(t.client().get_pool_stats().total_shares, 1_000_000);
+    assert_eq!(t.token().balance(&provider), 0);
+}
+
+#[test]
+fn test_repayment_fills_queue_at_current_share_price() {
+    let t = TestEnv::setup();
+    let provider = t.setup_locked(800);
+    let request_id = t.client().request_withdrawal(&provider, &500_000);
+
+    // 800 principal back + 100 interest (85 to LPs) → share price 1.085
+    t.mint(&t.creditline, 900);
+    t.client().receive_repayment(&t.creditline, &800, &100);
+
+    assert_eq!(t.client().get_claimable_withdrawal(&provider), 542);
+    assert_eq!(t.client().get_withdrawal_request(&request_id), None);
+    assert_eq!(t.client().get_pending_withdrawal(&provider), 0);
+    assert_eq!(t.client().get_withdrawal_queue_length(), 0);
+    let stats = t.client().get_pool_stats();
+    assert_eq!(stats.total_shares, 500_000);
+    assert_eq!(stats.total_liquidity, 1_085 - 542);
+}
+
+#[test]
+fn test_guarantee_fills_queue_in_part() {
+    let t = TestEnv::setup();
+    let provider = t.setup_locked(1_000);
+    let request_id = t.client().request_withdrawal(&provider, &200_000);
+
+    // 100 becomes available at a share price of 1.0 → 100_000 shares for 100
+    t.mint(&t.creditline, 100);
+    t.client().receive_guarantee(&t.creditline, &100);
+
+    assert_eq!(t.client().get_claimable_withdrawal(&provider), 100);
+    assert_eq!(t.client().get_pending_withdrawal(&provider), 100_000);
+    let request = t.client().get_withdrawal_request(&request_id).unwrap();
+    assert_eq!(request.shares, 100_000);
+    assert_eq!(t.client().get_withdrawal_queue_position(&request_id), 0);
+}
+
+#[test]
+fn test_process_withdrawal_queue_fills_in_fifo_order() {
+    let t = TestEnv::setup();
+    let first = t.setup_locked(800);
+    let second = Address::generate(&t.env);
+    t.mint(&second, 1_000);
+    t.client().deposit(&second, &1_000, &0);
+    let merchant = Address::generate(&t.env);
+    t.client().fund_loan(&t.creditline, &merchant, &1_000);
+
+    let first_id = t.client().request_withdrawal(&first, &300_000);
+    let second_id = t.client().request_withdrawal(&second, &300_000);
+    assert_eq!(t.client().get_withdrawal_queue_position(&second_id), 1);
+
+    // 200 available: the first request is filled in part, the second waits
+    assert_eq!(t.client().process_withdrawal_queue(&10), 0);
+    assert!(t.env.auths().is_empty());
+    assert_eq!(t.client().get_claimable_withdrawal(&first), 200);
+    assert_eq!(t.client().get_claimable_withdrawal(&second), 0);
+    let first_request = t.client().get_withdrawal_request(&first_id).unwrap();
+    assert_eq!(first_request.shares, 100_000);
+
+    // Freed liquidity completes both, oldest first
+    t.mint(&t.creditline, 800);
+    t.client().receive_repayment(&t.creditline, &800, &0);
+    assert_eq!(t.client().get_claimable_withdrawal(&first), 300);
+    assert_eq!(t.client().get_claimable_withdrawal(&second), 300);
+    assert_eq!(t.client().get_withdrawal_queue_length(), 0);
+    assert_eq!(t.client().get_queued_shares(), 0);
+}
+
+#[test]
+fn test_process_withdrawal_queue_respects_limit() {
+    let t = TestEnv::setup();
+    let provider = t.setup_locked(1);
+    t.client().request_withdrawal(&provider, &100_000);
+    let second_id = t.client().request_withdrawal(&provider, &100_000);
+
+    assert_eq!(t.client().process_withdrawal_queue(&1), 1);
+
+    assert_eq!(t.client().get_claimable_withdrawal(&provider), 100);
+    assert_eq!(t.client().get_withdrawal_queue_position(&second_id), 0);
+    assert_eq!(t.client().get_pending_withdrawal(&provider), 100_000);
+}
+
+#[test]
+fn test_request_withdrawal_emits_event() {
+    let t = TestEnv::setup();
+    let provider = t.setup_locked(800);
+
+    t.client().request_withdrawal(&provider, &500_000);
+
+    let events: Vec<(Address, Vec<Val>, Val)> = t.env.events().all();
+    let (_, topics, data) = events.last().unwrap();
+    let event_type: Symbol = topics.get(0).unwrap().into_val(&t.env);
+    let request_id: u64 = topics.get(2).unwrap().into_val(&t.env);
+    let shares: i128 = data.into_val(&t.env);
+    assert_eq!(event_type, symbol_short!("LQWQUEUE"));
+    assert_eq!(request_id, 0);
+    assert_eq!(shares, 500_000);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #13)")] // WithdrawalQueuePending
+fn test_withdraw_blocked_while_queue_pending() {
+    let t = TestEnv::setup();
+    let provider = t.setup_locked(800);
+    t.client().request_withdrawal(&provider, &500_000);
+
+    t.client().withdraw(&provider, &100_000);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #5)")] // InsufficientShares
+fn test_request_withdrawal_more_than_shares_fails() {
+    let t = TestEnv::setup();
+    let provider = t.setup_locked(800);
+
+    t.client().request_withdrawal(&provider, &1_000_001);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #14)")] // WithdrawalRequestNotFound
+fn test_queue_position_of_unknown_request_fails() {
+    let t = TestEnv::setup();
+
+    t.client().get_withdrawal_queue_position(&3);
+}
+
+#[test]
+fn test_claim_withdrawal_transfers_filled_tokens() {
+    let t = TestEnv::setup();
+    let provider = t.setup_locked(800);
+    t.client().request_withdrawal(&provider, &500_000);
+    t.mint(&t.creditline, 800);
+    t.client().receive_repayment(&t.creditline, &800, &0);
+    assert_eq!(t.token().balance(&provider), 0);
+
+    assert_eq!(t.client().claim_withdrawal(&provider), 500);
+
+    assert_eq!(t.token().balance(&provider), 500);
+    assert_eq!(t.client().get_claimable_withdrawal(&provider), 0);
+    assert_eq!(t.token().balance(&t.contract_id), 500);
+    let events: Vec<(Address, Vec<Val>, Val)> = t.env.events().all();
+    let (_, topics, data) = events.last().unwrap();
+    let event_type: Symbol = topics.get(0).unwrap().into_val(&t.env);
+    let amount: i128 = data.into_val(&t.env);
+    assert_eq!(event_type, symbol_short!("LQWQCLM"));
+    assert_eq!(amount, 500);
+}
+
+#[test]
+fn test_unclaimed_fills_are_not_paid_out_again() {
+    let t = TestEnv::setup();
+    let provider = t.setup_locked(1_000);
+    let second = t.deposit(1_000);
+    let merchant = Address::generate(&t.env);
+    t.client().fund_loan(&t.creditline, &merchant, &1_000);
+    t.client().request_withdrawal(&provider, &100_000);
+    t.client().request_withdrawal(&second, &200_000);
+
+    t.mint(&t.creditline, 100);
+    t.client().receive_guarantee(&t.creditline, &100);
+    t.client().process_withdrawal_queue(&10);
+
+    // The 100 credited to the first provider is theirs alone
+    assert_eq!(t.client().get_claimable_withdrawal(&provider), 100);
+    assert_eq!(t.client().get_claimable_withdrawal(&second), 0);
+}
+
+#[test]
+fn test_repayment_succeeds_when_provider_cannot_receive_tokens() {
+    let t = TestEnv::setup();
+    let provider = t.setup_locked(800);
+    t.client().request_withdrawal(&provider, &500_000);
+    let sac = StellarAssetClient::new(&t.env, &t.token_address);
+    sac.set_authorized(&provider, &false);
+
+    t.mint(&t.creditline, 800);
+    t.client().receive_repayment(&t.creditline, &800, &0);
+
+    assert_eq!(t.client().get_withdrawal_queue_length(), 0);
+    assert_eq!(t.client().get_claimable_withdrawal(&provider), 500);
+
+    sac.set_authorized(&provider, &true);
+    t.client().claim_withdrawal(&provider);
+    assert_eq!(t.token().balance(&provider), 500);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #22)")] // NothingToClaim
+fn test_claim_withdrawal_without_fills_fails() {
+    let t = TestEnv::setup();
+    let provider = t.setup_locked(800);
+    t.client().request_withdrawal(&provider, &500_000);
+
+    t.client().claim_withdrawal(&provider);
+}
+
+// ─── LP share token (SEP-41) ──────────────────────────────────────────────────
+
+impl TestEnv {
+    /// The pool viewed through the standard token interface.
+    fn shares(&self) -> TokenClient<'_> {
+        TokenClient::new(&self.env, &self.contract_id)
+    }
+
+    fn deposit(&self, amount: i128) -> Address {
+        let provider = Address::generate(&self.env);
+        self.mint(&provider, amount);
+        self.client().deposit(&provider, &amount, &0);
+        provider
+    }
+}
+
+#[test]
+fn test_share_token_metadata() {
+    let t = TestEnv::setup();
+
+    let default_name = String::from_str(&t.env, "TrustUp Pool Share");
+    assert_eq!(t.shares().name(), default_name);
+    assert_eq!(t.shares().symbol(), String::from_str(&t.env, "TUPLP"));
+    assert_eq!(t.shares().decimals(), t.token().decimals() + 3);
+
+    let name = String::from_str(&t.env, "TrustUp EURC Pool Share");
+    let symbol = String::from_str(&t.env, "TUPLPEUR");
+    t.client().set_share_metadata(&t.admin, &name, &symbol);
+    assert_eq!(t.shares().name(), name);
+    assert_eq!(t.shares().symbol(), symbol);
+}
+
+#[test]
+fn test_pool_from_before_share_offset_keeps_one_to_one_shares_until_empty() {
+    let t = TestEnv::setup();
+    let provider = Address::generate(&t.env);
+    // A first-release pool: 1_000 shares issued 1:1 and no decimals offset stored
+    t.mint(&t.contract_id, 1_000);
+    t.env.as_contract(&t.contract_id, || {
+        t.env
+            .storage()
+            .instance()
+            .remove(&crate::storage::SHARE_OFFSET_KEY);
+        crate::storage::set_total_shares(&t.env, 1_000);
+        crate::storage::set_total_liquidity(&t.env, 1_000);
+        crate::storage::set_lp_shares(&t.env, &provider, 1_000);
+    });
+    t.mint(&provider, 1_500);
+
+    assert_eq!(t.client().deposit(&provider, &500, &0), 500);
+    assert_eq!(t.client().get_pool_stats().share_price, 10_000);
+    assert_eq!(t.shares().decimals(), t.token().decimals());
+
+    // Once the old shares are gone the pool moves to the offset
+    t.client().withdraw(&provider, &1_500);
+    assert_eq!(t.client().deposit(&provider, &1_000, &0), 1_000_000);
+    assert_eq!(t.client().get_pool_stats().share_price, 10_000);
+    assert_eq!(t.shares().decimals(), t.token().decimals() + 3);
+}
+
+#[test]
+fn test_transferred_shares_can_be_withdrawn_by_recipient() {
+    let t = TestEnv::setup();
+    let provider = t.deposit(1_000);
+    let recipient = Address::generate(&t.env);
+
+    t.shares().transfer(&provider, &recipient, &400_000);
+
+    assert_eq!(t.shares().balance(&provider), 600_000);
+    assert_eq!(t.shares().balance(&recipient), 400_000);
+    assert_eq!(t.client().get_lp_shares(&recipient), 400_000);
+    assert_eq!(t.client().withdraw(&recipient, &400_000), 400);
+    assert_eq!(t.token().balance(&recipient), 400);
+}
+
+#[test]
+fn test_transfer_from_spends_allowance() {
+    let t = TestEnv::setup();
+    let provider = t.deposit(1_000);
+    let spender = Address::generate(&t.env);
+    let recipient = Address::generate(&t.env);
+    let expiration = t.env.ledger().sequence() + 100;
+
+    t.shares()
+        .approve(&provider, &spender, &500_000, &expiration);
+    t.shares()
+        .transfer_from(&spender, &provider, &recipient, &300_000);
+
+    assert_eq!(t.shares().allowance(&provider, &spender), 200_000);
+    assert_eq!(t.shares().balance(&recipient), 300_000);
+    assert_eq!(t.shares().balance(&provider), 700_000);
+}
+
+#[test]
+fn test_allowance_lapses_after_expiration() {
+    let t = TestEnv::setup();
+    let provider = t.deposit(1_000);
+    let spender = Address::generate(&t.env);
+    let expiration = t.env.ledger().sequence() + 10;
+    t.shares().approve(&provider, &spender, &500, &expiration);
+
+    t.env.ledger().set_sequence_number(expiration + 1);
+
+    assert_eq!(t.shares().allowance(&provider, &spender), 0);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #23)")] // BurnNotSupported
+fn test_burn_is_not_supported() {
+    let t = TestEnv::setup();
+    let provider = t.deposit(1_000);
+
+    t.shares().burn(&provider, &1_000_000);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #23)")] // BurnNotSupported
+fn test_burn_from_is_not_supported() {
+    let t = TestEnv::setup();
+    let provider = t.deposit(1_000);
+    let spender = Address::generate(&t.env);
+    let expiration = t.env.ledger().sequence() + 100;
+    t.shares()
+        .approve(&provider, &spender, &1_000_000, &expiration);
+
+    t.shares().burn_from(&spender, &provider, &1_000_000);
+}
+
+#[test]
+fn test_queued_shares_are_held_by_pool() {
+    let t = TestEnv::setup();
+    let provider = t.setup_locked(800);
+
+    t.client().request_withdrawal(&provider, &500_000);
+
+    assert_eq!(t.shares().balance(&t.contract_id), 500_000);
+    t.mint(&t.creditline, 800);
+    t.client().receive_repayment(&t.creditline, &800, &0);
+    assert_eq!(t.shares().balance(&t.contract_id), 0);
+}
+
+#[test]
+fn test_share_token_emits_standard_events() {
+    let t = TestEnv::setup();
+    let provider = t.deposit(1_000);
+    let events: Vec<(Address, Vec<Val>, Val)> = t.env.events().all();
+    let (_, topics, data) = events.last().unwrap();
+    let event_type: Symbol = topics.get(0).unwrap().into_val(&t.env);
+    let minted_to: Address = topics.get(2).unwrap().into_val(&t.env);
+    let amount: i128 = data.into_val(&t.env);
+    assert_eq!(event_type, symbol_short!("mint"));
+    assert_eq!(minted_to, provider);
+    assert_eq!(amount, 1_000_000);
+
+    let recipient = Address::generate(&t.env);
+    t.shares().transfer(&provider, &recipient, &250_000);
+
+    let events: Vec<(Address, Vec<Val>, Val)> = t.env.events().all();
+    let (_, topics, data) = events.last().unwrap();
+    let event_type: Symbol = topics.get(0).unwrap().into_val(&t.env);
+    let from: Address = topics.get(1).unwrap().into_val(&t.env);
+    let to: Address = topics.get(2).unwrap().into_val(&t.env);
+    let amount: i128 = data.into_val(&t.env);
+    assert_eq!(event_type, symbol_short!("transfer"));
+    assert_eq!((from, to, amount), (provider, recipient, 250_000));
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #15)")] // InsufficientAllowance
+fn test_transfer_from_beyond_allowance_fails() {
+    let t = TestEnv::setup();
+    let provider = t.deposit(1_000);
+    let spender = Address::generate(&t.env);
+    let expiration = t.env.ledger().sequence() + 100;
+    t.shares().approve(&provider, &spender, &100, &expiration);
+
+    t.shares()
+        .transfer_from(&spender, &provider, &spender, &101);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #16)")] // InvalidExpirationLedger
+fn test_approve_with_past_expiration_fails() {
+    let t = TestEnv::setup();
+    let provider = t.deposit(1_000);
+    let spender = Address::generate(&t.env);
+    t.env.ledger().set_sequence_number(50);
+
+    t.shares().approve(&provider, &spender, &100, &49);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #5)")] // InsufficientShares
+fn test_transfer_more_shares_than_held_fails() {
+    let t = TestEnv::setup();
+    let provider = t.deposit(1_000);
+
+    t.shares()
+        .transfer(&provider, &Address::generate(&t.env), &1_000_001);
 }
 
 // ─── withdraw (additional edge cases) ────────────────────────────────────────
@@ -560,10 +1382,10 @@ fn test_withdraw_returns_tokens_to_provider() {
     let provider = Address::generate(&t.env);
     t.mint(&provider, 2_000);
 
-    t.client().deposit(&provider, &2_000);
+    t.client().deposit(&provider, &2_000, &0);
     assert_eq!(t.token().balance(&provider), 0);
 
-    t.client().withdraw(&provider, &2_000);
+    t.client().withdraw(&provider, &2_000_000);
     assert_eq!(t.token().balance(&provider), 2_000);
 }
 
@@ -574,12 +1396,12 @@ fn test_withdraw_updates_pool_stats_correctly() {
     let provider = Address::generate(&t.env);
     t.mint(&provider, 3_000);
 
-    t.client().deposit(&provider, &3_000);
-    t.client().withdraw(&provider, &1_000);
+    t.client().deposit(&provider, &3_000, &0);
+    t.client().withdraw(&provider, &1_000_000);
 
     let stats = t.client().get_pool_stats();
     assert_eq!(stats.total_liquidity, 2_000);
-    assert_eq!(stats.total_shares, 2_000);
+    assert_eq!(stats.total_shares, 2_000_000);
     assert_eq!(stats.locked_liquidity, 0);
     assert_eq!(stats.available_liquidity, 2_000);
 }
@@ -594,22 +1416,22 @@ fn test_two_providers_independent_withdrawals() {
     t.mint(&provider_a, 1_000);
     t.mint(&provider_b, 2_000);
 
-    t.client().deposit(&provider_a, &1_000);
-    t.client().deposit(&provider_b, &2_000);
+    t.client().deposit(&provider_a, &1_000, &0);
+    t.client().deposit(&provider_b, &2_000, &0);
 
-    // A withdraws all their shares (1000 out of 3000 total = 1/3 of pool)
-    let returned_a = t.client().withdraw(&provider_a, &1_000);
+    // A withdraws all their shares (1_000_000 out of 3_000_000 total = 1/3 of pool)
+    let returned_a = t.client().withdraw(&provider_a, &1_000_000);
     assert_eq!(returned_a, 1_000);
     assert_eq!(t.client().get_lp_shares(&provider_a), 0);
 
     // B's shares and pool value are intact
-    assert_eq!(t.client().get_lp_shares(&provider_b), 2_000);
+    assert_eq!(t.client().get_lp_shares(&provider_b), 2_000_000);
     let stats = t.client().get_pool_stats();
     assert_eq!(stats.total_liquidity, 2_000);
-    assert_eq!(stats.total_shares, 2_000);
+    assert_eq!(stats.total_shares, 2_000_000);
 
     // B withdraws everything
-    let returned_b = t.client().withdraw(&provider_b, &2_000);
+    let returned_b = t.client().withdraw(&provider_b, &2_000_000);
     assert_eq!(returned_b, 2_000);
 
     let stats_final = t.client().get_pool_stats();
@@ -625,14 +1447,14 @@ fn test_withdraw_partial_when_some_liquidity_locked() {
     let provider = Address::generate(&t.env);
     let merchant = Address::generate(&t.env);
     t.mint(&provider, 1_000);
-    t.client().deposit(&provider, &1_000);
+    t.client().deposit(&provider, &1_000, &0);
 
     // Lock 400 tokens in a loan → 600 available
     t.client().fund_loan(&t.creditline, &merchant, &400);
 
     // Withdraw shares worth exactly 600 tokens (should pass)
-    // shares_to_withdraw = 600 * 1000 / 1000 = 600 shares
-    let returned = t.client().withdraw(&provider, &600);
+    // shares_to_withdraw = 600 * 1_001_000 / 1001 = 600_000 shares
+    let returned = t.client().withdraw(&provider, &600_000);
     assert_eq!(returned, 600);
 
     let stats = t.client().get_pool_stats();
@@ -647,7 +1469,7 @@ fn test_withdraw_negative_shares_fails() {
     let t = TestEnv::setup();
     let provider = Address::generate(&t.env);
     t.mint(&provider, 1_000);
-    t.client().deposit(&provider, &1_000);
+    t.client().deposit(&provider, &1_000, &0);
     t.client().withdraw(&provider, &-1);
 }
 
@@ -658,12 +1480,12 @@ fn test_sequential_partial_withdrawals_drain_pool() {
     let provider = Address::generate(&t.env);
     t.mint(&provider, 1_000);
 
-    t.client().deposit(&provider, &1_000);
+    t.client().deposit(&provider, &1_000, &0);
 
-    let first = t.client().withdraw(&provider, &600);
+    let first = t.client().withdraw(&provider, &600_000);
     assert_eq!(first, 600);
 
-    let second = t.client().withdraw(&provider, &400);
+    let second = t.client().withdraw(&provider, &400_000);
     assert_eq!(second, 400);
 
     assert_eq!(t.client().get_lp_shares(&provider), 0);
@@ -686,9 +1508,9 @@ fn test_withdraw_succeeds_after_loan_repayment_unlocks_liquidity() {
     let provider = Address::generate(&t.env);
     let merchant = Address::generate(&t.env);
     t.mint(&provider, 1_000);
-    t.client().deposit(&provider, &1_000);
+    t.client().deposit(&provider, &1_000, &0);
 
-    // Lock 600 tokens — only 400 remain available; a 1000-share withdrawal
+    // Lock 600 tokens — only 400 remain available; a 1_000_000-share withdrawal
     // (worth 1000 tokens) would exceed available_liquidity and fail.
     t.client().fund_loan(&t.creditline, &merchant, &600);
 
@@ -708,11 +1530,11 @@ fn test_withdraw_succeeds_after_loan_repayment_unlocks_liquidity() {
     // After the fund_loan → receive_repayment cycle with no interest:
     //   total_liquidity = 1000 (unchanged — fund_loan never decreases it,
     //                           and receive_repayment no longer adds principal back)
-    //   total_shares    = 1000
-    // Withdrawing 600 shares: 600 * 1000 / 1000 = 600 tokens.
-    let returned = t.client().withdraw(&provider, &600);
+    //   total_shares    = 1_000_000
+    // Withdrawing 600_000 shares: 600_000 * 1001 / 1_001_000 = 600 tokens.
+    let returned = t.client().withdraw(&provider, &600_000);
     assert_eq!(returned, 600);
-    assert_eq!(t.client().get_lp_shares(&provider), 400);
+    assert_eq!(t.client().get_lp_shares(&provider), 400_000);
 }
 
 // ─── pool_stats & calculate_withdrawal ───────────────────────────────────────
@@ -731,7 +1553,7 @@ fn test_get_pool_stats_empty_pool() {
 #[test]
 fn test_calculate_withdrawal_empty_pool_returns_zero() {
     let t = TestEnv::setup();
-    assert_eq!(t.client().calculate_withdrawal(&1_000), 0);
+    assert_eq!(t.client().calculate_withdrawal(&1_000_000), 0);
 }
 
 // ─── admin operations ─────────────────────────────────────────────────────────
@@ -759,10 +1581,10 @@ fn test_share_calculation_accuracy() {
 
     // Declare all test parameters as variables
     let provider1_deposit = 1_000_000;
-    let provider1_expected_shares = 1_000_000;
+    let provider1_expected_shares = 1_000_000_000;
     let provider2_deposit = 500_000;
-    let provider2_expected_shares = 500_000;
-    let expected_total_shares = 1_500_000;
+    let provider2_expected_shares = 500_000_000;
+    let expected_total_shares = 1_500_000_000;
     let expected_total_liquidity = 1_500_000;
     let interest_amount = 100_000;
     let principal_repayment = 0;
@@ -770,18 +1592,18 @@ fn test_share_calculation_accuracy() {
     let lp_interest = (interest_amount * lp_interest_percentage) / 100;
     let expected_liquidity_after_interest = expected_total_liquidity + lp_interest;
     let provider3_deposit = 100;
-    let provider3_expected_shares = 94;
+    let provider3_expected_shares = 94_637;
 
     // 1. Test with various deposit amounts (small, medium, large)
     let provider1 = Address::generate(&context.env);
     context.mint(&provider1, provider1_deposit);
-    let shares1 = context.client().deposit(&provider1, &provider1_deposit);
+    let shares1 = context.client().deposit(&provider1, &provider1_deposit, &0);
     assert_eq!(shares1, provider1_expected_shares);
 
     // 2. Test with different pool states - second deposit (proportional)
     let provider2 = Address::generate(&context.env);
     context.mint(&provider2, provider2_deposit);
-    let shares2 = context.client().deposit(&provider2, &provider2_deposit);
+    let shares2 = context.client().deposit(&provider2, &provider2_deposit, &0);
     assert_eq!(shares2, provider2_expected_shares);
 
     // 3. Verify no precision loss - total should match
@@ -805,7 +1627,7 @@ fn test_share_calculation_accuracy() {
     // Small deposit should round down correctly
     let provider3 = Address::generate(&context.env);
     context.mint(&provider3, provider3_deposit);
-    let shares3 = context.client().deposit(&provider3, &provider3_deposit);
+    let shares3 = context.client().deposit(&provider3, &provider3_deposit, &0);
     assert_eq!(shares3, provider3_expected_shares);
 }
 
@@ -815,12 +1637,12 @@ fn test_multiple_lp_deposits() {
 
     // Declare all test parameters as variables
     let provider1_deposit = 1000;
-    let provider1_expected_shares = 1000;
+    let provider1_expected_shares = 1_000_000;
     let provider2_deposit = 2000;
-    let provider2_expected_shares = 2000;
+    let provider2_expected_shares = 2_000_000;
     let provider3_deposit = 500;
-    let provider3_expected_shares = 500;
-    let expected_total_shares = 3500;
+    let provider3_expected_shares = 500_000;
+    let expected_total_shares = 3_500_000;
     let expected_total_liquidity = 3500;
     let expected_share_price = 10_000;
 
@@ -835,15 +1657,15 @@ fn test_multiple_lp_deposits() {
     context.mint(&provider3, provider3_deposit);
 
     // 3. Provider1 deposits tokens
-    let shares1 = context.client().deposit(&provider1, &provider1_deposit);
+    let shares1 = context.client().deposit(&provider1, &provider1_deposit, &0);
     assert_eq!(shares1, provider1_expected_shares);
 
     // 4. Provider2 deposits tokens
-    let shares2 = context.client().deposit(&provider2, &provider2_deposit);
+    let shares2 = context.client().deposit(&provider2, &provider2_deposit, &0);
     assert_eq!(shares2, provider2_expected_shares);
 
     // 5. Provider3 deposits tokens
-    let shares3 = context.client().deposit(&provider3, &provider3_deposit);
+    let shares3 = context.client().deposit(&provider3, &provider3_deposit, &0);
     assert_eq!(shares3, provider3_expected_shares);
 
     // 6. Verify each provider's share balance is correct
@@ -872,28 +1694,28 @@ fn test_withdrawal_with_active_loans() {
 
     // Declare all test parameters as variables
     let deposit_amount = 1000;
-    let expected_shares = 1000;
+    let expected_shares = 1_000_000;
     let loan_amount = 400;
     let expected_available_after_loan = 600;
     let expected_locked_after_loan = 400;
-    let withdrawal_shares = 600;
+    let withdrawal_shares = 600_000;
     let expected_withdrawn_amount = 600;
-    let expected_remaining_shares = 400;
+    let expected_remaining_shares = 400_000;
     let expected_final_liquidity = 400;
     let expected_final_available = 0;
     let expected_final_locked = 400;
-    let expected_final_shares = 400;
+    let expected_final_shares = 400_000;
     let expected_initial_liquidity = 1000;
     let expected_initial_available = 1000;
     let expected_initial_locked = 0;
-    let expected_initial_shares = 1000;
+    let expected_initial_shares = 1_000_000;
 
     // 1. Create a provider address and mint tokens
     let provider = Address::generate(&context.env);
     context.mint(&provider, deposit_amount);
 
     // 2. Provider deposits tokens
-    let shares = context.client().deposit(&provider, &deposit_amount);
+    let shares = context.client().deposit(&provider, &deposit_amount, &0);
     assert_eq!(shares, expected_shares);
 
     // Verify initial state
@@ -964,13 +1786,13 @@ fn test_withdrawal_with_active_loans_exceeds_available_shares() {
     // Declare all test parameters as variables
     let deposit_amount = 1000;
     let loan_amount = 400;
-    let first_withdrawal_shares = 600;
-    let second_withdrawal_shares = 500;
+    let first_withdrawal_shares = 600_000;
+    let second_withdrawal_shares = 500_000;
 
     // Setup: provider deposits tokens, loan locks liquidity, provider withdraws shares
     let provider = Address::generate(&context.env);
     context.mint(&provider, deposit_amount);
-    context.client().deposit(&provider, &deposit_amount);
+    context.client().deposit(&provider, &deposit_amount, &0);
 
     let merchant = Address::generate(&context.env);
     context
@@ -980,8 +1802,8 @@ fn test_withdrawal_with_active_loans_exceeds_available_shares() {
         .client()
         .withdraw(&provider, &first_withdrawal_shares);
 
-    // Now provider has 400 shares remaining, but available_liquidity is 0
-    // Attempt to withdraw 500 shares (more than remaining) - should fail with InsufficientShares
+    // Now provider has 400_000 shares remaining, but available_liquidity is 0
+    // Attempt to withdraw 500_000 shares (more than remaining) - should fail with InsufficientShares
     context
         .client()
         .withdraw(&provider, &second_withdrawal_shares);
@@ -995,13 +1817,13 @@ fn test_withdrawal_with_active_loans_no_available_liquidity() {
     // Declare all test parameters as variables
     let deposit_amount = 1000;
     let loan_amount = 400;
-    let first_withdrawal_shares = 600;
-    let second_withdrawal_shares = 100;
+    let first_withdrawal_shares = 600_000;
+    let second_withdrawal_shares = 100_000;
 
     // Setup: provider deposits tokens, loan locks liquidity, provider withdraws shares
     let provider = Address::generate(&context.env);
     context.mint(&provider, deposit_amount);
-    context.client().deposit(&provider, &deposit_amount);
+    context.client().deposit(&provider, &deposit_amount, &0);
 
     let merchant = Address::generate(&context.env);
     context
@@ -1011,7 +1833,7 @@ fn test_withdrawal_with_active_loans_no_available_liquidity() {
         .client()
         .withdraw(&provider, &first_withdrawal_shares);
 
-    // Now provider has 400 shares remaining, but available_liquidity is 0
+    // Now provider has 400_000 shares remaining, but available_liquidity is 0
     // Attempt to withdraw any amount when available_liquidity is 0 - should fail
     context
         .client()
@@ -1025,17 +1847,17 @@ fn test_share_value_maintained_after_withdrawal() {
     // Declare all test parameters as variables
     let provider1_deposit = 1000;
     let provider2_deposit = 1000;
-    let provider1_shares = 1000;
-    let provider2_shares = 1000;
+    let provider1_shares = 1_000_000;
+    let provider2_shares = 1_000_000;
     let expected_initial_liquidity = 2000;
-    let expected_initial_shares = 2000;
+    let expected_initial_shares = 2_000_000;
     let expected_initial_share_price = 10_000;
-    let provider1_withdrawal_shares = 1000;
+    let provider1_withdrawal_shares = 1_000_000;
     let expected_withdrawn1 = 1000;
     let expected_liquidity_after_withdrawal = 1000;
-    let expected_shares_after_withdrawal = 1000;
+    let expected_shares_after_withdrawal = 1_000_000;
     let expected_share_price_after_withdrawal = 10_000;
-    let provider2_withdrawal_shares = 1000;
+    let provider2_withdrawal_shares = 1_000_000;
     let expected_withdrawn2 = 1000;
     let expected_final_liquidity = 0;
     let expected_final_shares = 0;
@@ -1051,10 +1873,10 @@ fn test_share_value_maintained_after_withdrawal() {
     context.mint(&provider2, provider2_deposit);
 
     // 3. Both providers deposit equal amounts
-    let shares1 = context.client().deposit(&provider1, &provider1_deposit);
+    let shares1 = context.client().deposit(&provider1, &provider1_deposit, &0);
     assert_eq!(shares1, provider1_shares);
 
-    let shares2 = context.client().deposit(&provider2, &provider2_deposit);
+    let shares2 = context.client().deposit(&provider2, &provider2_deposit, &0);
     assert_eq!(shares2, provider2_shares);
 
     // 4. Verify initial state
@@ -1125,7 +1947,7 @@ fn test_share_value_appreciation_over_time() {
 
     // Declare all test parameters as variables
     let deposit_amount = 1000;
-    let expected_shares = 1000;
+    let expected_shares = 1_000_000;
     let expected_initial_share_price = 10_000;
     let interest_amount = 100;
     let principal_repayment = 0;
@@ -1137,15 +1959,16 @@ fn test_share_value_appreciation_over_time() {
     let expected_share_price_after_second = 11700;
     let expected_liquidity_after_third = expected_liquidity_after_second + lp_interest;
     let expected_share_price_after_third = 12550;
-    let withdrawal_shares = 1000;
-    let expected_final_withdrawal = expected_liquidity_after_third;
+    let withdrawal_shares = 1_000_000;
+    // Rounded down by the virtual share offset
+    let expected_final_withdrawal = expected_liquidity_after_third - 1;
     let new_provider_deposit = 1000;
-    let expected_new_provider_shares = 1000;
+    let expected_new_provider_shares = 1_000_000;
 
     // 1. Provider deposits tokens
     let provider = Address::generate(&context.env);
     context.mint(&provider, deposit_amount);
-    let shares = context.client().deposit(&provider, &deposit_amount);
+    let shares = context.client().deposit(&provider, &deposit_amount, &0);
     assert_eq!(shares, expected_shares);
 
     // 2. Record initial share_price
@@ -1213,8 +2036,8 @@ fn test_share_value_appreciation_over_time() {
     context.mint(&new_provider, new_provider_deposit);
     let new_shares = context
         .client()
-        .deposit(&new_provider, &new_provider_deposit);
-    // With empty pool, first deposit gets 1:1 ratio again
+        .deposit(&new_provider, &new_provider_deposit, &0);
+    // With empty pool, first deposit gets VIRTUAL_SHARES per token again
     assert_eq!(new_shares, expected_new_provider_shares);
 }
 
@@ -1230,15 +2053,15 @@ fn test_pool_empty_state_handling() {
     let expected_empty_locked = 0;
     let expected_empty_share_price = 10_000;
     let deposit_amount = 1000;
-    let expected_shares = 1000;
-    let withdrawal_shares = 1000;
+    let expected_shares = 1_000_000;
+    let withdrawal_shares = 1_000_000;
     let expected_returned_amount = 1000;
-    let calculation_shares = 1000;
+    let calculation_shares = 1_000_000;
     let expected_calculation_result = 0;
-    let second_deposit_amount = 500;
-    let expected_second_shares = 500;
-    let expected_final_liquidity = 500;
-    let expected_final_shares = 500;
+    let second_deposit_amount = 1000;
+    let expected_second_shares = 1_000_000;
+    let expected_final_liquidity = 1000;
+    let expected_final_shares = 1_000_000;
     let expected_final_share_price = 10_000;
 
     // 1. Verify initial empty pool stats
@@ -1251,7 +2074,7 @@ fn test_pool_empty_state_handling() {
     // 2. Create a provider, mint tokens, deposit them
     let provider = Address::generate(&context.env);
     context.mint(&provider, deposit_amount);
-    let shares = context.client().deposit(&provider, &deposit_amount);
+    let shares = context.client().deposit(&provider, &deposit_amount, &0);
     assert_eq!(shares, expected_shares);
 
     // Verify pool has liquidity after deposit
@@ -1274,12 +2097,14 @@ fn test_pool_empty_state_handling() {
     let withdrawal_calculation = context.client().calculate_withdrawal(&calculation_shares);
     assert_eq!(withdrawal_calculation, expected_calculation_result);
 
-    // 6. Verify next deposit after empty state works correctly (1:1 ratio)
+    // 6. Verify next deposit after empty state works correctly (VIRTUAL_SHARES per token)
     context.mint(&provider, second_deposit_amount);
-    let new_shares = context.client().deposit(&provider, &second_deposit_amount);
+    let new_shares = context
+        .client()
+        .deposit(&provider, &second_deposit_amount, &0);
     assert_eq!(new_shares, expected_second_shares);
 
-    // Verify final state shows correct 1:1 ratio
+    // Verify final state shows the same 1_000 shares per token
     let final_stats = context.client().get_pool_stats();
     assert_eq!(final_stats.total_liquidity, expected_final_liquidity);
     assert_eq!(final_stats.total_shares, expected_final_shares);
@@ -1292,7 +2117,7 @@ fn test_small_deposit_rounding() {
 
     // Declare all test parameters as variables
     let provider1_deposit = 1_000_000;
-    let provider1_expected_shares = 1_000_000;
+    let provider1_expected_shares = 1_000_000_000;
     let interest_amount = 200_000;
     let principal_repayment = 0;
     let lp_percentage = 85;
@@ -1300,15 +2125,15 @@ fn test_small_deposit_rounding() {
     let expected_liquidity_after_interest = provider1_deposit + lp_interest;
     let expected_share_price = 11700;
     let provider2_deposit = 100;
-    let provider2_expected_shares = 85;
-    let provider2_withdrawal_shares = 85;
+    let provider2_expected_shares = 85_470;
+    let provider2_withdrawal_shares = 85_470;
     let provider3_deposit = 10;
-    let provider3_expected_shares = 8;
+    let provider3_expected_shares = 8_547;
 
     // 1. Make a large initial deposit
     let provider1 = Address::generate(&context.env);
     context.mint(&provider1, provider1_deposit);
-    let shares1 = context.client().deposit(&provider1, &provider1_deposit);
+    let shares1 = context.client().deposit(&provider1, &provider1_deposit, &0);
     assert_eq!(shares1, provider1_expected_shares);
 
     // 2. Distribute interest to increase share_price
@@ -1325,7 +2150,7 @@ fn test_small_deposit_rounding() {
     // 3. Attempt very small deposits
     let provider2 = Address::generate(&context.env);
     context.mint(&provider2, provider2_deposit);
-    let shares2 = context.client().deposit(&provider2, &provider2_deposit);
+    let shares2 = context.client().deposit(&provider2, &provider2_deposit, &0);
 
     // 4. Verify shares are calculated correctly (rounded down)
     assert_eq!(shares2, provider2_expected_shares);
@@ -1339,7 +2164,7 @@ fn test_small_deposit_rounding() {
     // 6. Test edge case where deposit is very small but still gets shares
     let provider3 = Address::generate(&context.env);
     context.mint(&provider3, provider3_deposit);
-    let shares3 = context.client().deposit(&provider3, &provider3_deposit);
+    let shares3 = context.client().deposit(&provider3, &provider3_deposit, &0);
     assert_eq!(shares3, provider3_expected_shares);
 }
 
@@ -1349,45 +2174,45 @@ fn test_concurrent_deposits_and_withdrawals() {
 
     // Declare all test parameters as variables
     let provider1_deposit = 1000;
-    let provider1_expected_shares = 1000;
+    let provider1_expected_shares = 1_000_000;
     let provider2_deposit = 2000;
-    let provider2_expected_shares = 2000;
+    let provider2_expected_shares = 2_000_000;
     let provider3_deposit = 1500;
-    let provider3_expected_shares = 1500;
+    let provider3_expected_shares = 1_500_000;
     let expected_initial_liquidity = 4500;
-    let expected_initial_shares = 4500;
-    let provider1_withdrawal_shares = 500;
+    let expected_initial_shares = 4_500_000;
+    let provider1_withdrawal_shares = 500_000;
     let expected_withdrawn1 = 500;
     let expected_liquidity_after_withdrawal = 4000;
-    let expected_shares_after_withdrawal = 4000;
+    let expected_shares_after_withdrawal = 4_000_000;
     let interest_amount = 400;
     let principal_repayment = 0;
     let lp_percentage = 85;
     let lp_interest = (interest_amount * lp_percentage) / 100;
     let expected_liquidity_after_interest = expected_liquidity_after_withdrawal + lp_interest;
     let provider4_deposit = 1000;
-    let provider4_expected_shares = 921;
+    let provider4_expected_shares = 921_677;
     let expected_liquidity_after_provider4 = 5340;
-    let expected_shares_after_provider4 = 4921;
-    let provider2_withdrawal_shares = 2000;
-    let expected_withdrawn2 = 2170;
-    let expected_final_shares = 2921;
-    let expected_final_liquidity = 3170;
+    let expected_shares_after_provider4 = 4_921_677;
+    let provider2_withdrawal_shares = 2_000_000;
+    let expected_withdrawn2 = 2169;
+    let expected_final_shares = 2_921_677;
+    let expected_final_liquidity = 3171;
 
     // 1. Multiple providers deposit in sequence
     let provider1 = Address::generate(&context.env);
     context.mint(&provider1, provider1_deposit);
-    let shares1 = context.client().deposit(&provider1, &provider1_deposit);
+    let shares1 = context.client().deposit(&provider1, &provider1_deposit, &0);
     assert_eq!(shares1, provider1_expected_shares);
 
     let provider2 = Address::generate(&context.env);
     context.mint(&provider2, provider2_deposit);
-    let shares2 = context.client().deposit(&provider2, &provider2_deposit);
+    let shares2 = context.client().deposit(&provider2, &provider2_deposit, &0);
     assert_eq!(shares2, provider2_expected_shares);
 
     let provider3 = Address::generate(&context.env);
     context.mint(&provider3, provider3_deposit);
-    let shares3 = context.client().deposit(&provider3, &provider3_deposit);
+    let shares3 = context.client().deposit(&provider3, &provider3_deposit, &0);
     assert_eq!(shares3, provider3_expected_shares);
 
     // Verify initial state
@@ -1419,7 +2244,7 @@ fn test_concurrent_deposits_and_withdrawals() {
     // New provider deposits after interest
     let provider4 = Address::generate(&context.env);
     context.mint(&provider4, provider4_deposit);
-    let shares4 = context.client().deposit(&provider4, &provider4_deposit);
+    let shares4 = context.client().deposit(&provider4, &provider4_deposit, &0);
     assert_eq!(shares4, provider4_expected_shares);
 
     let stats4 = context.client().get_pool_stats();
@@ -1444,23 +2269,23 @@ fn test_loan_funding_reduces_available_liquidity() {
 
     // Declare all test parameters as variables
     let deposit_amount = 1000;
-    let expected_shares = 1000;
+    let expected_shares = 1_000_000;
     let expected_initial_liquidity = 1000;
     let expected_initial_available = 1000;
     let expected_initial_locked = 0;
-    let expected_initial_shares = 1000;
+    let expected_initial_shares = 1_000_000;
     let loan_amount = 400;
     let expected_locked_after_loan = 400;
     let expected_available_after_loan = 600;
     let expected_total_liquidity = 1000;
-    let expected_total_shares = 1000;
+    let expected_total_shares = 1_000_000;
 
     // 1. Create a provider address and mint tokens
     let provider = Address::generate(&context.env);
     context.mint(&provider, deposit_amount);
 
     // 2. Provider deposits tokens
-    let shares = context.client().deposit(&provider, &deposit_amount);
+    let shares = context.client().deposit(&provider, &deposit_amount, &0);
     assert_eq!(shares, expected_shares);
 
     // 3. Record initial pool stats
@@ -1525,7 +2350,7 @@ fn test_repayment_increases_pool_value() {
     // 1. Provider deposits tokens
     let provider = Address::generate(&context.env);
     context.mint(&provider, deposit_amount);
-    context.client().deposit(&provider, &deposit_amount);
+    context.client().deposit(&provider, &deposit_amount, &0);
 
     let initial_stats = context.client().get_pool_stats();
     assert_eq!(initial_stats.total_liquidity, expected_initial_liquidity);
@@ -1601,13 +2426,13 @@ fn test_guarantee_receipt_on_default() {
     let expected_total_liquidity_after_loan = 10000;
     let guarantee_amount = 3000;
     let expected_locked_after_guarantee = 2000;
-    let expected_total_liquidity_after_guarantee = 13000;
-    let expected_share_price_after_guarantee = 13000;
+    let expected_total_liquidity_after_guarantee = 10000;
+    let expected_share_price_after_guarantee = 10000;
 
     // 1. Provider deposits tokens
     let provider = Address::generate(&context.env);
     context.mint(&provider, deposit_amount);
-    context.client().deposit(&provider, &deposit_amount);
+    context.client().deposit(&provider, &deposit_amount, &0);
 
     let initial_stats = context.client().get_pool_stats();
     assert_eq!(initial_stats.total_liquidity, expected_initial_liquidity);
@@ -1646,7 +2471,7 @@ fn test_guarantee_receipt_on_default() {
         expected_locked_after_guarantee
     );
 
-    // 5. Verify total_liquidity increased by guarantee amount
+    // 5. Verify total_liquidity is unchanged (capital returned, not new value)
     assert_eq!(
         after_guarantee_stats.total_liquidity,
         expected_total_liquidity_after_guarantee
@@ -1673,25 +2498,25 @@ fn test_withdrawal_calculation_precision() {
 
     // Declare all test parameters as variables
     let initial_deposit = 10000;
-    let calc1_shares = 5000;
+    let calc1_shares = 5_000_000;
     let expected_calc1 = 5000;
-    let calc2_shares = 10000;
+    let calc2_shares = 10_000_000;
     let expected_calc2 = 10000;
-    let withdrawal1_shares = 5000;
+    let withdrawal1_shares = 5_000_000;
     let second_deposit = 10000;
     let interest_amount = 1000;
     let principal_repayment = 0;
     let expected_liquidity_after_interest = 15850;
-    let calc3_shares = 1000;
+    let calc3_shares = 1_000_000;
     let expected_calc3 = 1056;
-    let withdrawal2_shares = 1000;
-    let calc4_shares = 1;
-    let withdrawal3_shares = 1;
+    let withdrawal2_shares = 1_000_000;
+    let calc4_shares = 1_000;
+    let withdrawal3_shares = 1_000;
 
     // 1. Setup pool with various states
     let provider = Address::generate(&context.env);
     context.mint(&provider, initial_deposit);
-    context.client().deposit(&provider, &initial_deposit);
+    context.client().deposit(&provider, &initial_deposit, &0);
 
     // 2. Call calculate_withdrawal for different share amounts
     let calc1 = context.client().calculate_withdrawal(&calc1_shares);
@@ -1708,7 +2533,7 @@ fn test_withdrawal_calculation_precision() {
 
     // Deposit again for more tests
     context.mint(&provider, second_deposit);
-    context.client().deposit(&provider, &second_deposit);
+    context.client().deposit(&provider, &second_deposit, &0);
 
     // 5. Test with edge cases (very small/large amounts, after interest, etc.)
     // Distribute interest
@@ -1754,12 +2579,13 @@ fn test_share_price_calculation() {
     let lp_interest = (interest_amount * lp_percentage) / 100;
     let expected_liquidity_after_interest = deposit_amount + lp_interest;
     let expected_share_price_after_interest = 10850;
-    let withdrawal_shares = 2000;
-    let expected_share_price_after_withdrawal = 10850;
+    let withdrawal_shares = 2_000_000;
+    // Withdrawals round down in the pool's favour (virtual share offset)
+    let expected_share_price_after_withdrawal = 10853;
     let loop_interest_amount = 100;
     let loop_iterations = 5;
-    let expected_final_liquidity = 3680;
-    let expected_final_share_price = 12266;
+    let expected_final_liquidity = 3681;
+    let expected_final_share_price = 12270;
 
     // 1. Empty pool: share_price should be expected value
     let empty_stats = context.client().get_pool_stats();
@@ -1768,7 +2594,7 @@ fn test_share_price_calculation() {
     // 2. After first deposit: share_price should remain constant
     let provider = Address::generate(&context.env);
     context.mint(&provider, deposit_amount);
-    context.client().deposit(&provider, &deposit_amount);
+    context.client().deposit(&provider, &deposit_amount, &0);
 
     let after_deposit_stats = context.client().get_pool_stats();
     assert_eq!(
@@ -1837,13 +2663,13 @@ fn test_multiple_interest_distributions() {
     let expected_share_price_after_event4 = 13400;
     let expected_liquidity_after_event5 = 1425;
     let expected_share_price_after_event5 = 14250;
-    let withdrawal_shares = 1000;
-    let expected_withdrawn = 1425;
+    let withdrawal_shares = 1_000_000;
+    let expected_withdrawn = 1424; // 1_000_000 * 1426 / 1_001_000 (virtual share offset)
 
     // 1. Provider deposits tokens
     let provider = Address::generate(&context.env);
     context.mint(&provider, deposit_amount);
-    context.client().deposit(&provider, &deposit_amount);
+    context.client().deposit(&provider, &deposit_amount, &0);
 
     let initial_stats = context.client().get_pool_stats();
     assert_eq!(initial_stats.total_liquidity, expected_initial_liquidity);
@@ -1921,7 +2747,7 @@ fn test_zero_shares_edge_case() {
     // 1. Create pool with high share_price (large deposit + lots of interest)
     let provider1 = Address::generate(&context.env);
     context.mint(&provider1, provider1_deposit);
-    context.client().deposit(&provider1, &provider1_deposit);
+    context.client().deposit(&provider1, &provider1_deposit, &0);
 
     // Distribute large amount of interest multiple times
     for _ in 0..loop_iterations {
@@ -1947,11 +2773,11 @@ fn test_maximum_values_handling() {
 
     // Declare all test parameters as variables
     let large_amount = 1_000_000_000_000i128;
-    let expected_shares = large_amount;
+    let expected_shares = large_amount * 1_000;
     let expected_initial_liquidity = large_amount;
-    let expected_initial_shares = large_amount;
+    let expected_initial_shares = large_amount * 1_000;
     let expected_initial_share_price = 10_000;
-    let calc_shares = large_amount / 2;
+    let calc_shares = expected_shares / 2;
     let expected_calc = large_amount / 2;
     let large_interest = 100_000_000_000i128;
     let principal_repayment = 0;
@@ -1960,12 +2786,12 @@ fn test_maximum_values_handling() {
     let expected_liquidity_after_interest = large_amount + lp_interest;
     let expected_share_price_after_interest =
         (expected_liquidity_after_interest * 10_000) / large_amount;
-    let withdrawal_shares = large_amount;
+    let withdrawal_shares = expected_shares;
 
     // 1. Test with maximum reasonable token amounts
     let provider = Address::generate(&context.env);
     context.mint(&provider, large_amount);
-    let shares = context.client().deposit(&provider, &large_amount);
+    let shares = context.client().deposit(&provider, &large_amount, &0);
     assert_eq!(shares, expected_shares);
 
     // 2. Test share calculations with large numbers
@@ -1997,8 +2823,9 @@ fn test_maximum_values_handling() {
     );
 
     // Test withdrawal with large amounts
+    // Rounded down by the virtual share offset
     let withdrawn = context.client().withdraw(&provider, &withdrawal_shares);
-    assert_eq!(withdrawn, expected_liquidity_after_interest);
+    assert_eq!(withdrawn, expected_liquidity_after_interest - 1);
 }
 
 // ─── Admin Functions Tests ───────────────────────────────────────────────────
@@ -2013,7 +2840,7 @@ fn test_set_treasury() {
     // Verify by distributing interest and checking new treasury receives fees
     let provider = Address::generate(&t.env);
     t.mint(&provider, 1_000);
-    t.client().deposit(&provider, &1_000);
+    t.client().deposit(&provider, &1_000, &0);
 
     t.mint(&t.creditline, 100);
     t.client().receive_repayment(&t.creditline, &0, &100);
@@ -2042,7 +2869,7 @@ fn test_set_merchant_fund() {
     // Verify by distributing interest and checking new merchant fund receives fees
     let provider = Address::generate(&t.env);
     t.mint(&provider, 1_000);
-    t.client().deposit(&provider, &1_000);
+    t.client().deposit(&provider, &1_000, &0);
 
     t.mint(&t.creditline, 100);
     t.client().receive_repayment(&t.creditline, &0, &100);
@@ -2069,7 +2896,7 @@ fn test_receive_repayment_with_zero_principal() {
     let provider = Address::generate(&t.env);
     let merchant = Address::generate(&t.env);
     t.mint(&provider, 1_000);
-    t.client().deposit(&provider, &1_000);
+    t.client().deposit(&provider, &1_000, &0);
 
     // Fund a loan
     t.client().fund_loan(&t.creditline, &merchant, &500);
@@ -2094,7 +2921,7 @@ fn test_receive_repayment_with_zero_interest() {
     let provider = Address::generate(&t.env);
     let merchant = Address::generate(&t.env);
     t.mint(&provider, 1_000);
-    t.client().deposit(&provider, &1_000);
+    t.client().deposit(&provider, &1_000, &0);
 
     // Fund a loan
     t.client().fund_loan(&t.creditline, &merchant, &500);
@@ -2162,7 +2989,7 @@ fn test_receive_guarantee_exceeds_locked_liquidity() {
     let provider = Address::generate(&t.env);
     let merchant = Address::generate(&t.env);
     t.mint(&provider, 1_000);
-    t.client().deposit(&provider, &1_000);
+    t.client().deposit(&provider, &1_000, &0);
 
     // Fund a loan for 500
     t.client().fund_loan(&t.creditline, &merchant, &500);
@@ -2175,8 +3002,9 @@ fn test_receive_guarantee_exceeds_locked_liquidity() {
     let stats = t.client().get_pool_stats();
     // Locked reduced to 0 (capped at 500)
     assert_eq!(stats.locked_liquidity, 0);
-    // Total liquidity increases by recovered (500), not full amount (600)
-    assert_eq!(stats.total_liquidity, 1_500);
+    // Total liquidity is unchanged; only the recovered 500 is transferred
+    assert_eq!(stats.total_liquidity, 1_000);
+    assert_eq!(t.token().balance(&t.contract_id), 1_000);
 }
 
 // ─── fund_loan Edge Cases ────────────────────────────────────────────────────
@@ -2216,7 +3044,7 @@ fn test_multiple_loans_concurrent_funding() {
     let t = TestEnv::setup();
     let provider = Address::generate(&t.env);
     t.mint(&provider, 10_000);
-    t.client().deposit(&provider, &10_000);
+    t.client().deposit(&provider, &10_000, &0);
 
     let merchant1 = Address::generate(&t.env);
     let merchant2 = Address::generate(&t.env);
@@ -2238,7 +3066,7 @@ fn test_partial_guarantee_recovery_multiple_defaults() {
     let t = TestEnv::setup();
     let provider = Address::generate(&t.env);
     t.mint(&provider, 10_000);
-    t.client().deposit(&provider, &10_000);
+    t.client().deposit(&provider, &10_000, &0);
 
     let merchant1 = Address::generate(&t.env);
     let merchant2 = Address::generate(&t.env);
@@ -2253,7 +3081,7 @@ fn test_partial_guarantee_recovery_multiple_defaults() {
 
     let stats_after_first = t.client().get_pool_stats();
     assert_eq!(stats_after_first.locked_liquidity, 4_000); // 5000 - 1000
-    assert_eq!(stats_after_first.total_liquidity, 11_000); // 10000 + 1000
+    assert_eq!(stats_after_first.total_liquidity, 10_000); // unchanged
 
     // Second default with partial guarantee
     t.mint(&t.creditline, 800);
@@ -2261,7 +3089,8 @@ fn test_partial_guarantee_recovery_multiple_defaults() {
 
     let stats_after_second = t.client().get_pool_stats();
     assert_eq!(stats_after_second.locked_liquidity, 3_200); // 4000 - 800
-    assert_eq!(stats_after_second.total_liquidity, 11_800); // 11000 + 800
+    assert_eq!(stats_after_second.total_liquidity, 10_000); // unchanged
+    assert_eq!(stats_after_second.available_liquidity, 6_800);
 }
 
 #[test]
@@ -2274,8 +3103,8 @@ fn test_complex_lifecycle_deposits_loans_repayments_withdrawals() {
     t.mint(&provider1, 5_000);
     t.mint(&provider2, 3_000);
 
-    t.client().deposit(&provider1, &5_000);
-    t.client().deposit(&provider2, &3_000);
+    t.client().deposit(&provider1, &5_000, &0);
+    t.client().deposit(&provider2, &3_000, &0);
 
     // Fund loans
     let merchant = Address::generate(&t.env);
@@ -2330,7 +3159,7 @@ fn test_withdrawal_after_multiple_interest_distributions() {
     let provider = Address::generate(&t.env);
     t.mint(&provider, 1_000);
 
-    let shares = t.client().deposit(&provider, &1_000);
+    let shares = t.client().deposit(&provider, &1_000, &0);
 
     // Multiple interest distributions
     for _ in 0..5 {
@@ -2342,8 +3171,8 @@ fn test_withdrawal_after_multiple_interest_distributions() {
     let withdrawn = t.client().withdraw(&provider, &shares);
 
     // Should receive original + accumulated interest
-    // 5 * 85 (LP portion) = 425
-    assert_eq!(withdrawn, 1_425);
+    // 5 * 85 (LP portion) = 425, less 1 of rounding (virtual share offset)
+    assert_eq!(withdrawn, 1_424);
 }
 
 #[test]
@@ -2351,7 +3180,7 @@ fn test_loan_funding_and_guarantee_recovery_cycle() {
     let t = TestEnv::setup();
     let provider = Address::generate(&t.env);
     t.mint(&provider, 5_000);
-    t.client().deposit(&provider, &5_000);
+    t.client().deposit(&provider, &5_000, &0);
 
     let merchant = Address::generate(&t.env);
 
@@ -2364,14 +3193,14 @@ fn test_loan_funding_and_guarantee_recovery_cycle() {
 
     let stats_after_guarantee = t.client().get_pool_stats();
     assert_eq!(stats_after_guarantee.locked_liquidity, 1_500);
-    assert_eq!(stats_after_guarantee.total_liquidity, 5_500);
+    assert_eq!(stats_after_guarantee.total_liquidity, 5_000);
 
     // Fund another loan
     t.client().fund_loan(&t.creditline, &merchant, &1_000);
 
     let stats_final = t.client().get_pool_stats();
     assert_eq!(stats_final.locked_liquidity, 2_500);
-    assert_eq!(stats_final.available_liquidity, 3_000);
+    assert_eq!(stats_final.available_liquidity, 2_500);
 }
 
 // ─── pause / emergency stop ───────────────────────────────────────────────────
@@ -2406,7 +3235,7 @@ fn test_deposit_blocked_when_paused() {
     let provider = Address::generate(&t.env);
     t.mint(&provider, 1_000);
     t.client().pause(&t.admin);
-    t.client().deposit(&provider, &1_000);
+    t.client().deposit(&provider, &1_000, &0);
 }
 
 #[test]
@@ -2415,9 +3244,9 @@ fn test_withdraw_blocked_when_paused() {
     let t = TestEnv::setup();
     let provider = Address::generate(&t.env);
     t.mint(&provider, 1_000);
-    t.client().deposit(&provider, &1_000);
+    t.client().deposit(&provider, &1_000, &0);
     t.client().pause(&t.admin);
-    t.client().withdraw(&provider, &1_000);
+    t.client().withdraw(&provider, &1_000_000);
 }
 
 #[test]
@@ -2427,7 +3256,7 @@ fn test_fund_loan_blocked_when_paused() {
     let provider = Address::generate(&t.env);
     let merchant = Address::generate(&t.env);
     t.mint(&provider, 1_000);
-    t.client().deposit(&provider, &1_000);
+    t.client().deposit(&provider, &1_000, &0);
     t.client().pause(&t.admin);
     t.client().fund_loan(&t.creditline, &merchant, &500);
 }
@@ -2457,7 +3286,7 @@ fn test_distribute_interest_called_by_admin_splits_fees_correctly() {
     let t = TestEnv::setup();
     let provider = Address::generate(&t.env);
     t.mint(&provider, 10_000);
-    t.client().deposit(&provider, &10_000);
+    t.client().deposit(&provider, &10_000, &0);
 
     t.mint(&t.contract_id, 1_000);
     t.client().distribute_interest(&t.admin, &1_000);
@@ -2473,7 +3302,7 @@ fn test_distribute_interest_called_by_creditline_splits_fees_correctly() {
     let t = TestEnv::setup();
     let provider = Address::generate(&t.env);
     t.mint(&provider, 5_000);
-    t.client().deposit(&provider, &5_000);
+    t.client().deposit(&provider, &5_000, &0);
 
     t.mint(&t.contract_id, 200);
     t.client().distribute_interest(&t.creditline, &200);
@@ -2520,7 +3349,7 @@ fn test_distribute_interest_admin_and_creditline_both_authorized() {
     let t = TestEnv::setup();
     let provider = Address::generate(&t.env);
     t.mint(&provider, 10_000);
-    t.client().deposit(&provider, &10_000);
+    t.client().deposit(&provider, &10_000, &0);
 
     t.mint(&t.contract_id, 100);
     t.client().distribute_interest(&t.admin, &100);
@@ -2536,7 +3365,7 @@ fn test_distribute_interest_increases_share_price() {
     let t = TestEnv::setup();
     let provider = Address::generate(&t.env);
     t.mint(&provider, 1_000);
-    t.client().deposit(&provider, &1_000);
+    t.client().deposit(&provider, &1_000, &0);
 
     let before = t.client().get_pool_stats();
     assert_eq!(before.share_price, 10_000);
@@ -2556,7 +3385,7 @@ fn test_distribute_interest_lp_portion_stays_in_pool() {
     let t = TestEnv::setup();
     let provider = Address::generate(&t.env);
     t.mint(&provider, 2_000);
-    t.client().deposit(&provider, &2_000);
+    t.client().deposit(&provider, &2_000, &0);
 
     t.mint(&t.contract_id, 500);
 
@@ -2576,7 +3405,7 @@ fn test_distribute_interest_multiple_calls_compound_share_value() {
     let t = TestEnv::setup();
     let provider = Address::generate(&t.env);
     t.mint(&provider, 1_000);
-    t.client().deposit(&provider, &1_000);
+    t.client().deposit(&provider, &1_000, &0);
 
     for _ in 0..3 {
         t.mint(&t.contract_id, 100);
@@ -2600,17 +3429,17 @@ fn test_distribute_interest_proportional_to_multiple_lps() {
 
     for p in [&provider_a, &provider_b, &provider_c] {
         t.mint(p, 1_000);
-        t.client().deposit(p, &1_000);
+        t.client().deposit(p, &1_000, &0);
     }
 
     // Distribute 300 tokens: lp = 255 stays in pool
     t.mint(&t.contract_id, 300);
     t.client().distribute_interest(&t.admin, &300);
 
-    // total_liquidity = 3255, total_shares = 3000
-    // Each LP (1000 shares of 3000): 1000 * 3255 / 3000 = 1085
-    let val = t.client().calculate_withdrawal(&1_000);
-    assert_eq!(val, 1_085);
+    // total_liquidity = 3255, total_shares = 3_000_000
+    // Each LP (1_000_000 shares of 3_000_000): 1_000_000 * 3256 / 3_001_000 = 1084 (virtual offsets)
+    let val = t.client().calculate_withdrawal(&1_000_000);
+    assert_eq!(val, 1_084);
 }
 
 #[test]
@@ -2618,7 +3447,7 @@ fn test_distribute_interest_emits_interest_distributed_event() {
     let t = TestEnv::setup();
     let provider = Address::generate(&t.env);
     t.mint(&provider, 1_000);
-    t.client().deposit(&provider, &1_000);
+    t.client().deposit(&provider, &1_000, &0);
 
     t.mint(&t.contract_id, 100);
     t.client().distribute_interest(&t.admin, &100);
@@ -2650,7 +3479,7 @@ fn test_distribute_interest_rounding_remainder_to_merchant() {
     let t = TestEnv::setup();
     let provider = Address::generate(&t.env);
     t.mint(&provider, 10_000);
-    t.client().deposit(&provider, &10_000);
+    t.client().deposit(&provider, &10_000, &0);
 
     t.mint(&t.contract_id, 101);
     t.client().distribute_interest(&t.admin, &101);
@@ -2659,3 +3488,252 @@ fn test_distribute_interest_rounding_remainder_to_merchant() {
     // remainder = 101 - 85 - 10 = 6 goes to merchant (no dust lost to rounding)
     assert_eq!(t.token().balance(&t.merchant_fund), 6);
 }
+
+// ─── fee configuration ────────────────────────────────────────────────────────
+
+impl TestEnv {
+    fn fee_config(&self, lp: u32, protocol: u32, merchant: u32) -> FeeConfig {
+        FeeConfig {
+            lp_bps: lp,
+            protocol_bps: protocol,
+            merchant_bps: merchant,
+            reserve_bps: 0,
+            extra_recipients: Vec::new(&self.env),
+        }
+    }
+}
+
+#[test]
+fn test_default_fee_config() {
+    let t = TestEnv::setup();
+
+    assert_eq!(t.client().get_fee_config(), t.fee_config(8_500, 1_000, 500));
+}
+
+#[test]
+fn test_updated_fee_split_applies_to_interest() {
+    let t = TestEnv::setup();
+    t.deposit(1_000);
+    let config = t.fee_config(9_000, 700, 300);
+    t.client().set_fee_config(&t.admin, &config);
+
+    t.mint(&t.creditline, 1_000);
+    t.client().receive_repayment(&t.creditline, &0, &1_000);
+
+    assert_eq!(t.client().get_fee_config(), config);
+    assert_eq!(t.client().get_pool_stats().total_liquidity, 1_900);
+    assert_eq!(t.token().balance(&t.treasury), 70);
+    assert_eq!(t.token().balance(&t.merchant_fund), 30);
+}
+
+#[test]
+fn test_extra_fee_recipient_receives_its_share() {
+    let t = TestEnv::setup();
+    t.deposit(1_000);
+    let reserve = Address::generate(&t.env);
+    let mut config = t.fee_config(8_000, 1_000, 500);
+    config.extra_recipients = vec![
+        &t.env,
+        FeeRecipient {
+            recipient: reserve.clone(),
+            bps: 500,
+        },
+    ];
+    t.client().set_fee_config(&t.admin, &config);
+
+    t.mint(&t.creditline, 1_000);
+    t.client().receive_repayment(&t.creditline, &0, &1_000);
+
+    assert_eq!(t.token().balance(&reserve), 50);
+    assert_eq!(t.token().balance(&t.treasury), 100);
+    assert_eq!(t.token().balance(&t.merchant_fund), 50);
+    assert_eq!(t.client().get_pool_stats().total_liquidity, 1_800);
+}
+
+#[test]
+fn test_set_fee_config_emits_event() {
+    let t = TestEnv::setup();
+    let config = t.fee_config(8_000, 1_500, 500);
+
+    t.client().set_fee_config(&t.admin, &config);
+
+    let events: Vec<(Address, Vec<Val>, Val)> = t.env.events().all();
+    let (_, topics, data) = events.last().unwrap();
+    let event_type: Symbol = topics.get(0).unwrap().into_val(&t.env);
+    let event_config: FeeConfig = data.into_val(&t.env);
+    assert_eq!(event_type, symbol_short!("LQFEECFG"));
+    assert_eq!(event_config, config);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #19)")] // InvalidFeeConfig
+fn test_fee_config_not_summing_to_total_fails() {
+    let t = TestEnv::setup();
+
+    t.client()
+        .set_fee_config(&t.admin, &t.fee_config(8_500, 1_000, 400));
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #19)")] // InvalidFeeConfig
+fn test_fee_config_zero_share_recipient_fails() {
+    let t = TestEnv::setup();
+    let mut config = t.fee_config(8_500, 1_000, 500);
+    config.extra_recipients = vec![
+        &t.env,
+        FeeRecipient {
+            recipient: Address::generate(&t.env),
+            bps: 0,
+        },
+    ];
+
+    t.client().set_fee_config(&t.admin, &config);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #1)")] // NotAdmin
+fn test_set_fee_config_non_admin_fails() {
+    let t = TestEnv::setup();
+    let outsider = Address::generate(&t.env);
+
+    t.client()
+        .set_fee_config(&outsider, &t.fee_config(8_500, 1_000, 500));
+}
+
+// ─── insurance reserve ────────────────────────────────────────────────────────
+
+impl TestEnv {
+    fn top_up_reserve(&self, amount: i128) {
+        self.mint(&self.admin, amount);
+        self.client().top_up_reserve(&self.admin, &amount);
+    }
+}
+
+#[test]
+fn test_reserve_funded_by_interest_slice() {
+    let t = TestEnv::setup();
+    t.deposit(1_000);
+    let mut config = t.fee_config(8_000, 1_000, 500);
+    config.reserve_bps = 500;
+    t.client().set_fee_config(&t.admin, &config);
+
+    t.mint(&t.creditline, 1_000);
+    t.client().receive_repayment(&t.creditline, &0, &1_000);
+
+    let stats = t.client().get_pool_stats();
+    assert_eq!(stats.reserve_balance, 50);
+    assert_eq!(stats.total_liquidity, 1_800);
+    assert_eq!(t.token().balance(&t.merchant_fund), 50);
+    assert_eq!(t.token().balance(&t.contract_id), 1_850);
+}
+
+#[test]
+fn test_reserve_absorbs_default_loss_before_lps() {
+    let t = TestEnv::setup();
+    t.deposit(1_000);
+    t.top_up_reserve(300);
+    let merchant = Address::generate(&t.env);
+    t.client().fund_loan(&t.creditline, &merchant, &500);
+
+    t.client().write_off(&t.creditline, &200);
+
+    let stats = t.client().get_pool_stats();
+    assert_eq!(stats.reserve_balance, 100);
+    assert_eq!(stats.total_liquidity, 1_000);
+    assert_eq!(stats.share_price, 10_000);
+    assert_eq!(stats.total_losses, 0);
+
+    // Once the reserve is exhausted, LP capital takes the rest
+    t.client().write_off(&t.creditline, &300);
+
+    let stats = t.client().get_pool_stats();
+    assert_eq!(stats.reserve_balance, 0);
+    assert_eq!(stats.total_liquidity, 800);
+    assert_eq!(stats.total_losses, 200);
+    assert_eq!(stats.locked_liquidity, 0);
+}
+
+#[test]
+fn test_reserve_absorption_emits_event() {
+    let t = TestEnv::setup();
+    t.deposit(1_000);
+    t.top_up_reserve(300);
+    let merchant = Address::generate(&t.env);
+    t.client().fund_loan(&t.creditline, &merchant, &500);
+
+    t.client().write_off(&t.creditline, &400);
+
+    let events: Vec<(Address, Vec<Val>, Val)> = t.env.events().all();
+    let absorbed = events.iter().find(|(_, topics, _)| {
+        let event_type: Symbol = topics.get(0).unwrap().into_val(&t.env);
+        event_type == symbol_short!("LQRSVABS")
+    });
+    let (_, _, data) = absorbed.expect("reserve absorption event");
+    let (amount, reserve_left): (i128, i128) = data.into_val(&t.env);
+    assert_eq!((amount, reserve_left), (300, 0));
+    assert_eq!(t.client().get_pool_stats().total_losses, 100);
+}
+
+#[test]
+fn test_reserve_coverage_ratio() {
+    let t = TestEnv::setup();
+    t.deposit(1_000);
+    assert_eq!(t.client().get_pool_stats().reserve_coverage_bps, 0);
+
+    t.top_up_reserve(100);
+    let merchant = Address::generate(&t.env);
+    t.client().fund_loan(&t.creditline, &merchant, &400);
+
+    let stats = t.client().get_pool_stats();
+    assert_eq!(stats.reserve_balance, 100);
+    assert_eq!(stats.reserve_coverage_bps, 2_500);
+    // The reserve is not LP capital
+    assert_eq!(stats.total_liquidity, 1_000);
+}
+
+#[test]
+fn test_withdraw_reserve_sends_tokens() {
+    let t = TestEnv::setup();
+    t.top_up_reserve(500);
+    let recipient = Address::generate(&t.env);
+
+    t.client().withdraw_reserve(&t.admin, &recipient, &200);
+
+    assert_eq!(t.token().balance(&recipient), 200);
+    assert_eq!(t.client().get_pool_stats().reserve_balance, 300);
+    assert_eq!(t.token().balance(&t.contract_id), 300);
+}
+
+#[test]
+fn test_withdrawal_queue_does_not_spend_reserve() {
+    let t = TestEnv::setup();
+    let provider = t.setup_locked(1_000);
+    t.top_up_reserve(500);
+    t.client().request_withdrawal(&provider, &100_000);
+
+    t.mint(&t.creditline, 100);
+    t.client().receive_guarantee(&t.creditline, &100);
+
+    // Only the 100 guarantee is free to pay out, as without a reserve
+    assert_eq!(t.client().get_claimable_withdrawal(&provider), 100);
+    assert_eq!(t.client().get_pool_stats().reserve_balance, 500);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #20)")] // InsufficientReserve
+fn test_withdraw_reserve_beyond_balance_fails() {
+    let t = TestEnv::setup();
+    t.top_up_reserve(100);
+
+    t.client().withdraw_reserve(&t.admin, &t.admin, &101);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #1)")] // NotAdmin
+fn test_top_up_reserve_non_admin_fails() {
+    let t = TestEnv::setup();
+    let outsider = Address::generate(&t.env);
+    t.mint(&outsider, 100);
+
+    t.client().top_up_reserve(&outsider, &100);
+}
@@ -1,4 +1,4 @@
-use soroban_sdk::contracttype;
+use soroban_sdk::{contracttype, Address, Vec};
 
 /// Pool statistics returned by get_pool_stats
 #[contracttype]
@@ -8,16 +8,83 @@ pub struct PoolStats {
     pub locked_liquidity: i128,
     pub available_liquidity: i128,
     pub total_shares: i128,
-    /// Share price expressed in basis points (10000 = $1.00)
+    /// Value of `10^SHARE_DECIMALS_OFFSET` shares in basis points (10000 = $1.00)
     pub share_price: i128,
+    /// Cumulative principal losses borne by LP capital (after the reserve)
+    pub total_losses: i128,
+    /// First-loss insurance reserve, held outside LP capital
+    pub reserve_balance: i128,
+    /// Reserve as a share of locked liquidity in basis points (0 when nothing is lent)
+    pub reserve_coverage_bps: i128,
 }
 
-// Fee split constants (basis points, sum = 10000)
-pub const LP_FEE_BPS: i128 = 8500; // 85% to liquidity providers
-pub const PROTOCOL_FEE_BPS: i128 = 1000; // 10% to protocol treasury
-#[allow(dead_code)]
-pub const MERCHANT_FEE_BPS: i128 = 500; // 5% to merchant incentive fund (used as remainder to avoid rounding loss)
+/// A queued LP withdrawal; `shares` is what remains to be filled
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WithdrawalRequest {
+    pub provider: Address,
+    pub shares: i128,
+    pub requested_at: u64,
+}
+
+/// An LP share allowance granted through `approve`
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AllowanceValue {
+    pub amount: i128,
+    pub expiration_ledger: u32,
+}
+
+/// An additional interest recipient (e.g. an insurance reserve)
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FeeRecipient {
+    pub recipient: Address,
+    pub bps: u32,
+}
+
+/// How interest is split; all shares together must sum to `TOTAL_BPS`.
+/// `reserve_bps` funds the pool's first-loss insurance reserve. The merchant
+/// fund also takes the rounding remainder.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FeeConfig {
+    pub lp_bps: u32,
+    pub protocol_bps: u32,
+    pub merchant_bps: u32,
+    pub reserve_bps: u32,
+    pub extra_recipients: Vec<FeeRecipient>,
+}
+
+// Default fee split (basis points, sum = 10000)
+pub const LP_FEE_BPS: u32 = 8500; // 85% to liquidity providers
+pub const PROTOCOL_FEE_BPS: u32 = 1000; // 10% to protocol treasury
+pub const MERCHANT_FEE_BPS: u32 = 500; // 5% to merchant incentive fund (used as remainder to avoid rounding loss)
 pub const TOTAL_BPS: i128 = 10000;
+/// Maximum number of extra interest recipients
+pub const MAX_FEE_RECIPIENTS: u32 = 5;
 
 /// Minimum deposit / withdrawal to prevent rounding exploits
 pub const MIN_AMOUNT: i128 = 1;
+
+/// Decimal places LP shares carry beyond the underlying token. Pools
+/// initialised before the offset existed carry none until they next empty.
+pub const SHARE_DECIMALS_OFFSET: u32 = 3;
+/// Virtual offsets in the share price: a permanent holder of
+/// `10^SHARE_DECIMALS_OFFSET` shares backed by one token unit. An empty pool
+/// issues 1_000 shares per token, so inflating the share price enough to
+/// round a later deposit down costs the attacker ~1_000 times the victim's
+/// loss, which the virtual shares keep.
+pub const VIRTUAL_SHARES: i128 = 10_i128.pow(SHARE_DECIMALS_OFFSET);
+pub const VIRTUAL_ASSETS: i128 = 1;
+/// Smallest deposit accepted into an empty pool
+pub const MIN_FIRST_DEPOSIT: i128 = 1_000;
+
+/// Default SEP-41 metadata of the LP share token
+pub const SHARE_NAME: &str = "TrustUp Pool Share";
+pub const SHARE_SYMBOL: &str = "TUPLP";
+
+/// Queued withdrawals filled after each repayment / guarantee received
+pub const AUTO_FILL_LIMIT: u32 = 5;
+/// Maximum queued withdrawals filled per `process_withdrawal_queue` call
+pub const MAX_QUEUE_BATCH: u32 = 50;
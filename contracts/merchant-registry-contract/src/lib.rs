@@ -48,7 +48,7 @@ impl MerchantRegistryContract {
             return Err(Error::MerchantAlreadyRegistered);
         }
 
-        if name.len() == 0 || name.len() > 64 {
+        if name.is_empty() || name.len() > 64 {
             return Err(Error::InvalidName);
         }
 
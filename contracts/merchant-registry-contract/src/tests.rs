@@ -54,7 +54,7 @@ fn test_registration_flow() {
     let info = client.get_merchant_info(&merchant);
     assert_eq!(info.name, name);
     assert_eq!(info.registration_date, 1000000);
-    assert_eq!(info.active, true);
+    assert!(info.active);
     assert_eq!(info.total_sales, 0);
     assert_eq!(client.get_merchant_count(), 1);
 }
@@ -102,12 +102,12 @@ fn test_activation_and_deactivation() {
     // Deactivate merchant
     client.deactivate_merchant(&admin, &merchant);
     assert!(!client.is_active(&merchant));
-    assert_eq!(client.get_merchant_info(&merchant).active, false);
+    assert!(!client.get_merchant_info(&merchant).active);
 
     // Activate merchant
     client.activate_merchant(&admin, &merchant);
     assert!(client.is_active(&merchant));
-    assert_eq!(client.get_merchant_info(&merchant).active, true);
+    assert!(client.get_merchant_info(&merchant).active);
 }
 
 #[test]
@@ -7,4 +7,6 @@ pub enum ParametersError {
     AlreadyInitialized = 1,
     NotAdmin = 2,
     InvalidParameters = 3,
+    InvalidCreditLimitTiers = 4,
+    InvalidPricingModel = 5,
 }
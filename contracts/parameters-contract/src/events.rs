@@ -1,9 +1,11 @@
-use soroban_sdk::{symbol_short, Address, Env, Symbol};
+use soroban_sdk::{symbol_short, Address, Env, Symbol, Vec};
 
-use crate::types::ProtocolParameters;
+use crate::types::{CreditLimitTier, PricingModel, ProtocolParameters};
 
 const PARAMS_UPDATED: Symbol = symbol_short!("PARMUPDT");
 const ADMIN_UPDATED: Symbol = symbol_short!("PARMADMN");
+const LIMIT_TIERS_UPDATED: Symbol = symbol_short!("LIMTIERS");
+const PRICING_UPDATED: Symbol = symbol_short!("PRICING");
 
 pub fn emit_parameters_updated(env: &Env, admin: &Address, params: &ProtocolParameters) {
     env.events().publish(
@@ -15,7 +17,6 @@ pub fn emit_parameters_updated(env: &Env, admin: &Address, params: &ProtocolPara
             params.default_penalty,
             params.large_loan_threshold,
             params.large_loan_default_penalty,
-            params.base_interest_bps,
         ),
     );
 }
@@ -24,3 +25,13 @@ pub fn emit_admin_updated(env: &Env, old_admin: &Address, new_admin: &Address) {
     env.events()
         .publish((ADMIN_UPDATED, old_admin), new_admin.clone());
 }
+
+pub fn emit_credit_limit_tiers_updated(env: &Env, admin: &Address, tiers: &Vec<CreditLimitTier>) {
+    env.events()
+        .publish((LIMIT_TIERS_UPDATED, admin), tiers.clone());
+}
+
+pub fn emit_pricing_model_updated(env: &Env, admin: &Address, model: &PricingModel) {
+    env.events()
+        .publish((PRICING_UPDATED, admin), model.clone());
+}
@@ -7,9 +7,13 @@ mod storage;
 mod types;
 
 pub use errors::ParametersError;
-pub use types::{default_parameters, ProtocolParameters};
+pub use types::{
+    default_credit_limit_tiers, default_parameters, default_pricing_model, CreditLimitTier,
+    LateFeeBase, PricingModel, ProtocolParameters, RateTier, TenorPremium, DAYS_PER_YEAR,
+    MAX_CREDIT_LIMIT_TIERS, MAX_PRICING_TIERS,
+};
 
-use soroban_sdk::{contract, contractimpl, panic_with_error, Address, Env};
+use soroban_sdk::{contract, contractimpl, panic_with_error, Address, Env, Vec};
 
 #[contract]
 pub struct ParametersContract;
@@ -59,10 +63,110 @@ impl ParametersContract {
         events::emit_parameters_updated(&env, &admin, &params);
     }
 
+    /// Credit-limit table ordered by ascending `min_score`.  Falls back to
+    /// `default_credit_limit_tiers` until governance sets one.
+    pub fn get_credit_limit_tiers(env: Env) -> Vec<CreditLimitTier> {
+        storage::get_credit_limit_tiers(&env).unwrap_or_else(|| default_credit_limit_tiers(&env))
+    }
+
+    pub fn set_credit_limit_tiers(env: Env, admin: Address, tiers: Vec<CreditLimitTier>) {
+        admin.require_auth();
+        access::require_admin(&env, &admin);
+        Self::validate_credit_limit_tiers(&env, &tiers);
+
+        storage::set_credit_limit_tiers(&env, &tiers);
+        events::emit_credit_limit_tiers_updated(&env, &admin, &tiers);
+    }
+
+    /// Tiers must be non-empty, strictly increasing in `min_score` and
+    /// non-decreasing in `max_exposure`, so a higher score never lowers the limit.
+    fn validate_credit_limit_tiers(env: &Env, tiers: &Vec<CreditLimitTier>) {
+        if tiers.is_empty() || tiers.len() > MAX_CREDIT_LIMIT_TIERS {
+            panic_with_error!(env, ParametersError::InvalidCreditLimitTiers);
+        }
+
+        let mut previous: Option<CreditLimitTier> = None;
+        for tier in tiers.iter() {
+            if tier.max_exposure < 0 {
+                panic_with_error!(env, ParametersError::InvalidCreditLimitTiers);
+            }
+            if let Some(prev) = previous {
+                if tier.min_score <= prev.min_score || tier.max_exposure < prev.max_exposure {
+                    panic_with_error!(env, ParametersError::InvalidCreditLimitTiers);
+                }
+            }
+            previous = Some(tier);
+        }
+    }
+
+    /// Interest pricing table.  Until governance sets one, first-release
+    /// parameters price flat at their `base_interest_bps` and everything else
+    /// falls back to `default_pricing_model`.
+    pub fn get_pricing_model(env: Env) -> PricingModel {
+        storage::get_pricing_model(&env).unwrap_or_else(|| default_pricing_model(&env))
+    }
+
+    pub fn set_pricing_model(env: Env, admin: Address, model: PricingModel) {
+        admin.require_auth();
+        access::require_admin(&env, &admin);
+        Self::validate_pricing_model(&env, &model);
+
+        storage::set_pricing_model(&env, &model);
+        events::emit_pricing_model_updated(&env, &admin, &model);
+    }
+
+    /// Score tiers must be non-empty, strictly increasing in `min_score` and
+    /// non-increasing in `rate_bps`; tenor buckets strictly increasing in
+    /// `min_tenor_seconds` and non-decreasing in `premium_bps`; and the bounds
+    /// must satisfy `min_rate_bps <= max_rate_bps <= 10_000`.
+    fn validate_pricing_model(env: &Env, model: &PricingModel) {
+        if model.rate_tiers.is_empty()
+            || model.rate_tiers.len() > MAX_PRICING_TIERS
+            || model.tenor_premiums.len() > MAX_PRICING_TIERS
+            || model.min_rate_bps > model.max_rate_bps
+            || model.max_rate_bps > 10_000
+        {
+            panic_with_error!(env, ParametersError::InvalidPricingModel);
+        }
+
+        let mut previous: Option<RateTier> = None;
+        for tier in model.rate_tiers.iter() {
+            if let Some(prev) = previous {
+                if tier.min_score <= prev.min_score || tier.rate_bps > prev.rate_bps {
+                    panic_with_error!(env, ParametersError::InvalidPricingModel);
+                }
+            }
+            previous = Some(tier);
+        }
+
+        let mut previous: Option<TenorPremium> = None;
+        for bucket in model.tenor_premiums.iter() {
+            if let Some(prev) = previous {
+                if bucket.min_tenor_seconds <= prev.min_tenor_seconds
+                    || bucket.premium_bps < prev.premium_bps
+                {
+                    panic_with_error!(env, ParametersError::InvalidPricingModel);
+                }
+            }
+            previous = Some(bucket);
+        }
+    }
+
     fn validate_parameters(env: &Env, params: &ProtocolParameters) {
         if params.min_guarantee_percent <= 0
             || params.min_guarantee_percent > 100
             || params.large_loan_threshold <= 0
+            || params.max_installments == 0
+            || params.max_tenor_seconds == 0
+            || params.revolving_cycle_seconds == 0
+            || params.early_payoff_rebate_bps > 10_000
+            || params.keeper_bounty_bps > 10_000
+            || params.recovery_score_restore_bps > 10_000
+            || params.late_fee_bps_per_day > 10_000
+            || params.max_late_fee_bps > 10_000
+            || params.revolving_min_payment_bps > 10_000
+            || (params.late_fee_bps_per_day as u64) * (DAYS_PER_YEAR as u64)
+                > params.late_fee_apr_cap_bps as u64
         {
             panic_with_error!(env, ParametersError::InvalidParameters);
         }
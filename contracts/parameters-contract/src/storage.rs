@@ -1,9 +1,11 @@
-use soroban_sdk::{symbol_short, Address, Env, Symbol};
+use soroban_sdk::{symbol_short, Address, Env, Map, Symbol, TryFromVal, Val, Vec};
 
-use crate::types::ProtocolParameters;
+use crate::types::{CreditLimitTier, PricingModel, ProtocolParameters, ProtocolParametersV0};
 
 pub const ADMIN_KEY: Symbol = symbol_short!("ADMIN");
 pub const PARAMS_KEY: Symbol = symbol_short!("PARAMS");
+pub const LIMIT_TIERS_KEY: Symbol = symbol_short!("LIMTIERS");
+pub const PRICING_KEY: Symbol = symbol_short!("PRICING");
 
 pub fn has_admin(env: &Env) -> bool {
     env.storage().instance().has(&ADMIN_KEY)
@@ -20,13 +22,62 @@ pub fn set_admin(env: &Env, admin: &Address) {
     env.storage().instance().set(&ADMIN_KEY, admin);
 }
 
+/// Parameters stored by the first release still carry `base_interest_bps`;
+/// they are read as `ProtocolParametersV0` and upgraded.  The field is checked
+/// before decoding because a failed struct decode traps on chain.
 pub fn get_parameters(env: &Env) -> ProtocolParameters {
-    env.storage()
+    let raw: Map<Symbol, Val> = env
+        .storage()
         .instance()
         .get(&PARAMS_KEY)
-        .expect("parameters not set")
+        .expect("parameters not set");
+
+    if raw.contains_key(Symbol::new(env, "base_interest_bps")) {
+        ProtocolParametersV0::try_from_val(env, raw.as_val())
+            .expect("unreadable parameters")
+            .upgrade()
+    } else {
+        ProtocolParameters::try_from_val(env, raw.as_val()).expect("unreadable parameters")
+    }
 }
 
+/// Overwriting first-release parameters would lose `base_interest_bps`, so
+/// the flat pricing model it implies is stored first unless governance has
+/// already set one.
 pub fn set_parameters(env: &Env, params: &ProtocolParameters) {
+    if !env.storage().instance().has(&PRICING_KEY) {
+        if let Some(legacy) = get_legacy_parameters(env) {
+            set_pricing_model(env, &legacy.pricing_model(env));
+        }
+    }
     env.storage().instance().set(&PARAMS_KEY, params);
 }
+
+fn get_legacy_parameters(env: &Env) -> Option<ProtocolParametersV0> {
+    let raw: Map<Symbol, Val> = env.storage().instance().get(&PARAMS_KEY)?;
+    if !raw.contains_key(Symbol::new(env, "base_interest_bps")) {
+        return None;
+    }
+    Some(ProtocolParametersV0::try_from_val(env, raw.as_val()).expect("unreadable parameters"))
+}
+
+pub fn get_credit_limit_tiers(env: &Env) -> Option<Vec<CreditLimitTier>> {
+    env.storage().instance().get(&LIMIT_TIERS_KEY)
+}
+
+pub fn set_credit_limit_tiers(env: &Env, tiers: &Vec<CreditLimitTier>) {
+    env.storage().instance().set(&LIMIT_TIERS_KEY, tiers);
+}
+
+/// Until governance sets a model, first-release parameters price at their
+/// `base_interest_bps`.
+pub fn get_pricing_model(env: &Env) -> Option<PricingModel> {
+    env.storage()
+        .instance()
+        .get(&PRICING_KEY)
+        .or_else(|| get_legacy_parameters(env).map(|legacy| legacy.pricing_model(env)))
+}
+
+pub fn set_pricing_model(env: &Env, model: &PricingModel) {
+    env.storage().instance().set(&PRICING_KEY, model);
+}
@@ -1,5 +1,10 @@
-use crate::{default_parameters, ParametersContract, ParametersContractClient, ProtocolParameters};
-use soroban_sdk::{testutils::Address as _, Address, Env};
+use crate::{
+    default_credit_limit_tiers, default_parameters, default_pricing_model, CreditLimitTier,
+    LateFeeBase, ParametersContract, ParametersContractClient, PricingModel, ProtocolParameters,
+    RateTier, TenorPremium,
+};
+use crate::{storage, types::ProtocolParametersV0};
+use soroban_sdk::{testutils::Address as _, vec, Address, Env};
 
 fn setup() -> (Env, ParametersContractClient<'static>, Address) {
     let env = Env::default();
@@ -34,8 +39,20 @@ fn test_update_parameters() {
         default_penalty: 25,
         large_loan_threshold: 7_500,
         large_loan_default_penalty: 40,
-        base_interest_bps: 900,
         grace_period_seconds: 86_400,
+        pending_loan_ttl_seconds: 172_800,
+        max_installments: 6,
+        max_tenor_seconds: 15_552_000,
+        early_payoff_rebate_bps: 5_000,
+        early_payoff_min_lead_seconds: 1_209_600,
+        keeper_bounty_bps: 500,
+        recovery_score_restore_bps: 2_500,
+        late_fee_bps_per_day: 10,
+        late_fee_base: LateFeeBase::Principal,
+        max_late_fee_bps: 1_000,
+        late_fee_apr_cap_bps: 3_650,
+        revolving_cycle_seconds: 1_209_600,
+        revolving_min_payment_bps: 1_000,
     };
 
     client.update_parameters(&admin, &params);
@@ -65,3 +82,363 @@ fn test_invalid_parameters_rejected() {
 
     client.initialize(&admin, &params);
 }
+
+#[test]
+#[should_panic(expected = "Error(Contract, #3)")]
+fn test_zero_max_installments_rejected() {
+    let (_env, client, admin) = setup();
+    client.initialize_defaults(&admin);
+
+    let params = ProtocolParameters {
+        max_installments: 0,
+        ..default_parameters()
+    };
+
+    client.update_parameters(&admin, &params);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #3)")]
+fn test_zero_revolving_cycle_rejected() {
+    let (_env, client, admin) = setup();
+    client.initialize_defaults(&admin);
+
+    let params = ProtocolParameters {
+        revolving_cycle_seconds: 0,
+        ..default_parameters()
+    };
+
+    client.update_parameters(&admin, &params);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #3)")]
+fn test_revolving_min_payment_above_100_percent_rejected() {
+    let (_env, client, admin) = setup();
+    client.initialize_defaults(&admin);
+
+    let params = ProtocolParameters {
+        revolving_min_payment_bps: 10_001,
+        ..default_parameters()
+    };
+
+    client.update_parameters(&admin, &params);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #3)")]
+fn test_early_payoff_rebate_above_100_percent_rejected() {
+    let (_env, client, admin) = setup();
+    client.initialize_defaults(&admin);
+
+    let params = ProtocolParameters {
+        early_payoff_rebate_bps: 10_001,
+        ..default_parameters()
+    };
+
+    client.update_parameters(&admin, &params);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #3)")]
+fn test_late_fee_rate_above_apr_cap_rejected() {
+    let (_env, client, admin) = setup();
+    client.initialize_defaults(&admin);
+
+    // 11 bps/day annualises to 4_015 bps, above the 3_650 cap
+    let params = ProtocolParameters {
+        late_fee_bps_per_day: 11,
+        late_fee_apr_cap_bps: 3_650,
+        ..default_parameters()
+    };
+
+    client.update_parameters(&admin, &params);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #3)")]
+fn test_max_late_fee_above_principal_rejected() {
+    let (_env, client, admin) = setup();
+    client.initialize_defaults(&admin);
+
+    let params = ProtocolParameters {
+        max_late_fee_bps: 10_001,
+        ..default_parameters()
+    };
+
+    client.update_parameters(&admin, &params);
+}
+
+#[test]
+fn test_credit_limit_tiers_default_until_set() {
+    let (env, client, admin) = setup();
+    client.initialize_defaults(&admin);
+
+    assert_eq!(
+        client.get_credit_limit_tiers(),
+        default_credit_limit_tiers(&env)
+    );
+}
+
+#[test]
+fn test_set_credit_limit_tiers() {
+    let (env, client, admin) = setup();
+    client.initialize_defaults(&admin);
+
+    let tiers = vec![
+        &env,
+        CreditLimitTier {
+            min_score: 50,
+            max_exposure: 5_000_000_000,
+        },
+        CreditLimitTier {
+            min_score: 80,
+            max_exposure: 20_000_000_000,
+        },
+    ];
+
+    client.set_credit_limit_tiers(&admin, &tiers);
+    assert_eq!(client.get_credit_limit_tiers(), tiers);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #2)")]
+fn test_non_admin_cannot_set_credit_limit_tiers() {
+    let (env, client, admin) = setup();
+    client.initialize_defaults(&admin);
+
+    let intruder = Address::generate(&env);
+    client.set_credit_limit_tiers(&intruder, &default_credit_limit_tiers(&env));
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #4)")]
+fn test_credit_limit_tiers_with_unsorted_scores_rejected() {
+    let (env, client, admin) = setup();
+    client.initialize_defaults(&admin);
+
+    let tiers = vec![
+        &env,
+        CreditLimitTier {
+            min_score: 75,
+            max_exposure: 1_000,
+        },
+        CreditLimitTier {
+            min_score: 75,
+            max_exposure: 2_000,
+        },
+    ];
+
+    client.set_credit_limit_tiers(&admin, &tiers);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #4)")]
+fn test_credit_limit_tiers_with_decreasing_exposure_rejected() {
+    let (env, client, admin) = setup();
+    client.initialize_defaults(&admin);
+
+    let tiers = vec![
+        &env,
+        CreditLimitTier {
+            min_score: 0,
+            max_exposure: 2_000,
+        },
+        CreditLimitTier {
+            min_score: 80,
+            max_exposure: 1_000,
+        },
+    ];
+
+    client.set_credit_limit_tiers(&admin, &tiers);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #4)")]
+fn test_empty_credit_limit_tiers_rejected() {
+    let (env, client, admin) = setup();
+    client.initialize_defaults(&admin);
+
+    client.set_credit_limit_tiers(&admin, &vec![&env]);
+}
+
+fn sample_pricing_model(env: &Env) -> PricingModel {
+    PricingModel {
+        rate_tiers: vec![
+            env,
+            RateTier {
+                min_score: 0,
+                rate_bps: 1_500,
+            },
+            RateTier {
+                min_score: 80,
+                rate_bps: 700,
+            },
+        ],
+        tenor_premiums: vec![
+            env,
+            TenorPremium {
+                min_tenor_seconds: 7_776_000,
+                premium_bps: 150,
+            },
+        ],
+        min_rate_bps: 300,
+        max_rate_bps: 2_000,
+    }
+}
+
+#[test]
+fn test_pricing_model_default_until_set() {
+    let (env, client, admin) = setup();
+    client.initialize_defaults(&admin);
+
+    assert_eq!(client.get_pricing_model(), default_pricing_model(&env));
+}
+
+#[test]
+fn test_set_pricing_model() {
+    let (env, client, admin) = setup();
+    client.initialize_defaults(&admin);
+
+    let model = sample_pricing_model(&env);
+    client.set_pricing_model(&admin, &model);
+    assert_eq!(client.get_pricing_model(), model);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #2)")]
+fn test_non_admin_cannot_set_pricing_model() {
+    let (env, client, admin) = setup();
+    client.initialize_defaults(&admin);
+
+    let intruder = Address::generate(&env);
+    client.set_pricing_model(&intruder, &sample_pricing_model(&env));
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #5)")]
+fn test_pricing_model_with_rate_rising_with_score_rejected() {
+    let (env, client, admin) = setup();
+    client.initialize_defaults(&admin);
+
+    let mut model = sample_pricing_model(&env);
+    model.rate_tiers.set(
+        1,
+        RateTier {
+            min_score: 80,
+            rate_bps: 1_600,
+        },
+    );
+
+    client.set_pricing_model(&admin, &model);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #5)")]
+fn test_pricing_model_with_unsorted_tenor_buckets_rejected() {
+    let (env, client, admin) = setup();
+    client.initialize_defaults(&admin);
+
+    let mut model = sample_pricing_model(&env);
+    model.tenor_premiums.push_back(TenorPremium {
+        min_tenor_seconds: 2_592_000,
+        premium_bps: 300,
+    });
+
+    client.set_pricing_model(&admin, &model);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #5)")]
+fn test_pricing_model_with_inverted_bounds_rejected() {
+    let (env, client, admin) = setup();
+    client.initialize_defaults(&admin);
+
+    let model = PricingModel {
+        min_rate_bps: 2_500,
+        ..sample_pricing_model(&env)
+    };
+
+    client.set_pricing_model(&admin, &model);
+}
+
+/// Store parameters as the first release wrote them
+fn store_first_release_parameters(env: &Env, client: &ParametersContractClient) {
+    let legacy = ProtocolParametersV0 {
+        min_guarantee_percent: 30,
+        min_reputation_threshold: 70,
+        full_repayment_reward: 12,
+        default_penalty: 25,
+        large_loan_threshold: 7_500,
+        large_loan_default_penalty: 40,
+        base_interest_bps: 500,
+        grace_period_seconds: 86_400,
+    };
+    env.as_contract(&client.address, || {
+        env.storage().instance().set(&storage::PARAMS_KEY, &legacy);
+    });
+}
+
+#[test]
+fn test_first_release_parameters_are_upgraded_on_read() {
+    let (env, client, admin) = setup();
+    client.initialize_defaults(&admin);
+
+    store_first_release_parameters(&env, &client);
+
+    assert_eq!(
+        client.get_parameters(),
+        ProtocolParameters {
+            min_guarantee_percent: 30,
+            min_reputation_threshold: 70,
+            full_repayment_reward: 12,
+            default_penalty: 25,
+            large_loan_threshold: 7_500,
+            large_loan_default_penalty: 40,
+            grace_period_seconds: 86_400,
+            ..default_parameters()
+        }
+    );
+
+    // The next update stores the current layout
+    client.update_parameters(&admin, &default_parameters());
+    assert_eq!(client.get_parameters(), default_parameters());
+}
+
+#[test]
+fn test_first_release_base_rate_seeds_the_pricing_model() {
+    let (env, client, admin) = setup();
+    client.initialize_defaults(&admin);
+
+    store_first_release_parameters(&env, &client);
+
+    let flat = PricingModel {
+        rate_tiers: vec![
+            &env,
+            RateTier {
+                min_score: 0,
+                rate_bps: 500,
+            },
+        ],
+        tenor_premiums: vec![&env],
+        min_rate_bps: 0,
+        max_rate_bps: 10_000,
+    };
+    assert_eq!(client.get_pricing_model(), flat);
+
+    // Replacing the legacy layout keeps the base rate
+    client.update_parameters(&admin, &default_parameters());
+    assert_eq!(client.get_pricing_model(), flat);
+}
+
+#[test]
+fn test_first_release_base_rate_does_not_replace_a_governed_model() {
+    let (env, client, admin) = setup();
+    client.initialize_defaults(&admin);
+    client.set_pricing_model(&admin, &sample_pricing_model(&env));
+
+    store_first_release_parameters(&env, &client);
+
+    client.update_parameters(&admin, &default_parameters());
+    assert_eq!(client.get_pricing_model(), sample_pricing_model(&env));
+}
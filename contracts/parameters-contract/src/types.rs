@@ -1,4 +1,52 @@
-use soroban_sdk::contracttype;
+use soroban_sdk::{contracttype, vec, Env, Vec};
+
+/// Balance that daily late fees are charged against.
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum LateFeeBase {
+    /// Everything still owed, including previously accrued late fees.
+    RemainingBalance,
+    /// Outstanding principal only.
+    Principal,
+}
+
+/// One row of the credit-limit table: borrowers scoring at least `min_score`
+/// may carry up to `max_exposure` in active debt.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CreditLimitTier {
+    pub min_score: u32,
+    pub max_exposure: i128,
+}
+
+/// Score band of the pricing table: borrowers scoring at least `min_score`
+/// pay `rate_bps` of the principal as interest.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RateTier {
+    pub min_score: u32,
+    pub rate_bps: u32,
+}
+
+/// Tenor bucket of the pricing table: loans running at least
+/// `min_tenor_seconds` pay an extra `premium_bps` on top of their score rate.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TenorPremium {
+    pub min_tenor_seconds: u64,
+    pub premium_bps: u32,
+}
+
+/// Interest pricing: the score rate plus any tenor premium, clamped to
+/// `[min_rate_bps, max_rate_bps]`.  Scores below every tier pay `max_rate_bps`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PricingModel {
+    pub rate_tiers: Vec<RateTier>,
+    pub tenor_premiums: Vec<TenorPremium>,
+    pub min_rate_bps: u32,
+    pub max_rate_bps: u32,
+}
 
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -9,11 +57,95 @@ pub struct ProtocolParameters {
     pub default_penalty: u32,
     pub large_loan_threshold: i128,
     pub large_loan_default_penalty: u32,
-    pub base_interest_bps: u32,
     /// Seconds after the last installment due date before a hard default can be triggered.
     /// During this window the borrower can still repay (with late fees) and no reputation
     /// penalty is applied yet.  Set to 0 to disable the grace period.
     pub grace_period_seconds: u64,
+    /// Seconds a `Pending` loan request may wait for approval before anyone can
+    /// expire it and refund the escrowed guarantee.  Set to 0 to never expire.
+    pub pending_loan_ttl_seconds: u64,
+    /// Maximum number of installments a repayment schedule may contain.
+    pub max_installments: u32,
+    /// Maximum seconds between loan creation and the last installment due date.
+    pub max_tenor_seconds: u64,
+    /// Share of unearned interest (bps) rebated when a loan is paid off early.
+    /// Set to 0 to disable the early-payoff rebate.
+    pub early_payoff_rebate_bps: u32,
+    /// Minimum seconds before the final installment due date a payoff must land
+    /// to qualify for the rebate.
+    pub early_payoff_min_lead_seconds: u64,
+    /// Share of a forfeited guarantee (bps) paid to the keeper that triggers the
+    /// default through `mark_defaulted_batch`.  Set to 0 to disable.
+    pub keeper_bounty_bps: u32,
+    /// Share of the default penalty (bps) given back to the borrower's reputation
+    /// once a defaulted loan is repaid in full.  Set to 0 to disable.
+    pub recovery_score_restore_bps: u32,
+    /// Late fee charged per complete overdue day (bps of the accrual base).
+    pub late_fee_bps_per_day: u32,
+    /// Balance the daily late fee is charged against.
+    pub late_fee_base: LateFeeBase,
+    /// Ceiling on the late fees a single loan can accrue over its life (bps of
+    /// the principal).
+    pub max_late_fee_bps: u32,
+    /// Annualised ceiling on the late-fee rate (bps per 365 days).  Parameters
+    /// whose daily rate exceeds it over 365 days are rejected, and the daily
+    /// rate is clamped to it when fees accrue.
+    pub late_fee_apr_cap_bps: u32,
+    /// Length of a revolving credit line billing cycle; a statement is closed
+    /// at the end of each one.
+    pub revolving_cycle_seconds: u64,
+    /// Minimum payment due on each revolving statement (bps of the closing
+    /// balance), and never less than the interest charged in the cycle.
+    pub revolving_min_payment_bps: u32,
+}
+
+/// `ProtocolParameters` as stored by the first release.  Never written:
+/// `storage::get_parameters` upgrades it when it finds one.
+#[contracttype(export = false)]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ProtocolParametersV0 {
+    pub min_guarantee_percent: i128,
+    pub min_reputation_threshold: u32,
+    pub full_repayment_reward: u32,
+    pub default_penalty: u32,
+    pub large_loan_threshold: i128,
+    pub large_loan_default_penalty: u32,
+    pub base_interest_bps: u32,
+    pub grace_period_seconds: u64,
+}
+
+impl ProtocolParametersV0 {
+    /// Keep the governed values and take defaults for every later setting.
+    /// `base_interest_bps` moves to the pricing model, see `pricing_model`.
+    pub fn upgrade(self) -> ProtocolParameters {
+        ProtocolParameters {
+            min_guarantee_percent: self.min_guarantee_percent,
+            min_reputation_threshold: self.min_reputation_threshold,
+            full_repayment_reward: self.full_repayment_reward,
+            default_penalty: self.default_penalty,
+            large_loan_threshold: self.large_loan_threshold,
+            large_loan_default_penalty: self.large_loan_default_penalty,
+            grace_period_seconds: self.grace_period_seconds,
+            ..default_parameters()
+        }
+    }
+
+    /// Flat pricing at the governed `base_interest_bps`, so upgrading does not
+    /// change what borrowers pay until governance sets a pricing model.
+    pub fn pricing_model(&self, env: &Env) -> PricingModel {
+        PricingModel {
+            rate_tiers: vec![
+                env,
+                RateTier {
+                    min_score: 0,
+                    rate_bps: self.base_interest_bps,
+                },
+            ],
+            tenor_premiums: Vec::new(env),
+            min_rate_bps: 0,
+            max_rate_bps: 10_000,
+        }
+    }
 }
 
 pub const DEFAULT_MIN_GUARANTEE_PERCENT: i128 = 20;
@@ -22,10 +154,90 @@ pub const DEFAULT_FULL_REPAYMENT_REWARD: u32 = 10;
 pub const DEFAULT_DEFAULT_PENALTY: u32 = 20;
 pub const DEFAULT_LARGE_LOAN_THRESHOLD: i128 = 5_000;
 pub const DEFAULT_LARGE_LOAN_DEFAULT_PENALTY: u32 = 30;
-pub const DEFAULT_BASE_INTEREST_BPS: u32 = 0;
 /// Default grace period: disabled (0).  Set via governance to enable, e.g.
 /// 259_200 for a 3-day window.
 pub const DEFAULT_GRACE_PERIOD_SECONDS: u64 = 0;
+/// Default pending request lifetime: 7 days.
+pub const DEFAULT_PENDING_LOAN_TTL_SECONDS: u64 = 604_800;
+pub const DEFAULT_MAX_INSTALLMENTS: u32 = 12;
+/// Default maximum tenor: 365 days.
+pub const DEFAULT_MAX_TENOR_SECONDS: u64 = 31_536_000;
+/// Default early-payoff rebate: disabled (0).
+pub const DEFAULT_EARLY_PAYOFF_REBATE_BPS: u32 = 0;
+/// Default early-payoff lead time: 7 days.
+pub const DEFAULT_EARLY_PAYOFF_MIN_LEAD_SECONDS: u64 = 604_800;
+/// Default keeper bounty: disabled (0).
+pub const DEFAULT_KEEPER_BOUNTY_BPS: u32 = 0;
+/// Default recovery restoration: half of the default penalty.
+pub const DEFAULT_RECOVERY_SCORE_RESTORE_BPS: u32 = 5_000;
+/// Default late fee: 0.5% per overdue day.
+pub const DEFAULT_LATE_FEE_BPS_PER_DAY: u32 = 50;
+pub const DEFAULT_LATE_FEE_BASE: LateFeeBase = LateFeeBase::RemainingBalance;
+/// Default late-fee ceiling: 25% of principal.
+pub const DEFAULT_MAX_LATE_FEE_BPS: u32 = 2_500;
+/// Default APR cap: the default daily rate annualised (50 × 365).
+pub const DEFAULT_LATE_FEE_APR_CAP_BPS: u32 = 18_250;
+/// Default billing cycle: 30 days.
+pub const DEFAULT_REVOLVING_CYCLE_SECONDS: u64 = 2_592_000;
+/// Default revolving minimum payment: 5% of the statement balance.
+pub const DEFAULT_REVOLVING_MIN_PAYMENT_BPS: u32 = 500;
+/// Days used to annualise the daily late-fee rate.
+pub const DAYS_PER_YEAR: u32 = 365;
+
+/// Upper bound on the number of rows in the credit-limit table.
+pub const MAX_CREDIT_LIMIT_TIERS: u32 = 10;
+
+pub fn default_credit_limit_tiers(env: &Env) -> Vec<CreditLimitTier> {
+    vec![
+        env,
+        CreditLimitTier {
+            min_score: 0,
+            max_exposure: 1_000,
+        },
+        CreditLimitTier {
+            min_score: 60,
+            max_exposure: 2_500,
+        },
+        CreditLimitTier {
+            min_score: 75,
+            max_exposure: 5_000,
+        },
+        CreditLimitTier {
+            min_score: 90,
+            max_exposure: 10_000,
+        },
+    ]
+}
+
+/// Upper bound on the number of score tiers and of tenor buckets.
+pub const MAX_PRICING_TIERS: u32 = 10;
+
+pub fn default_pricing_model(env: &Env) -> PricingModel {
+    PricingModel {
+        rate_tiers: vec![
+            env,
+            RateTier {
+                min_score: 0,
+                rate_bps: 1_000,
+            },
+            RateTier {
+                min_score: 60,
+                rate_bps: 800,
+            },
+            RateTier {
+                min_score: 75,
+                rate_bps: 600,
+            },
+            RateTier {
+                min_score: 90,
+                rate_bps: 400,
+            },
+        ],
+        tenor_premiums: Vec::new(env),
+        min_rate_bps: 0,
+        max_rate_bps: 10_000,
+    }
+}
 
 pub fn default_parameters() -> ProtocolParameters {
     ProtocolParameters {
@@ -35,7 +247,19 @@ pub fn default_parameters() -> ProtocolParameters {
         default_penalty: DEFAULT_DEFAULT_PENALTY,
         large_loan_threshold: DEFAULT_LARGE_LOAN_THRESHOLD,
         large_loan_default_penalty: DEFAULT_LARGE_LOAN_DEFAULT_PENALTY,
-        base_interest_bps: DEFAULT_BASE_INTEREST_BPS,
         grace_period_seconds: DEFAULT_GRACE_PERIOD_SECONDS,
+        pending_loan_ttl_seconds: DEFAULT_PENDING_LOAN_TTL_SECONDS,
+        max_installments: DEFAULT_MAX_INSTALLMENTS,
+        max_tenor_seconds: DEFAULT_MAX_TENOR_SECONDS,
+        early_payoff_rebate_bps: DEFAULT_EARLY_PAYOFF_REBATE_BPS,
+        early_payoff_min_lead_seconds: DEFAULT_EARLY_PAYOFF_MIN_LEAD_SECONDS,
+        keeper_bounty_bps: DEFAULT_KEEPER_BOUNTY_BPS,
+        recovery_score_restore_bps: DEFAULT_RECOVERY_SCORE_RESTORE_BPS,
+        late_fee_bps_per_day: DEFAULT_LATE_FEE_BPS_PER_DAY,
+        late_fee_base: DEFAULT_LATE_FEE_BASE,
+        max_late_fee_bps: DEFAULT_MAX_LATE_FEE_BPS,
+        late_fee_apr_cap_bps: DEFAULT_LATE_FEE_APR_CAP_BPS,
+        revolving_cycle_seconds: DEFAULT_REVOLVING_CYCLE_SECONDS,
+        revolving_min_payment_bps: DEFAULT_REVOLVING_MIN_PAYMENT_BPS,
     }
 }
@@ -1,10 +1,11 @@
-use soroban_sdk::{testutils::Address as _, Address, Env, String};
+use soroban_sdk::{testutils::Address as _, Address, Env};
 use soroban_sdk::token::{Client as TokenClient, StellarAssetClient};
 use creditline_contract::{CreditLineContract, CreditLineContractClient};
 use liquidity_pool_contract::{LiquidityPoolContract, LiquidityPoolContractClient};
 use merchant_registry_contract::{MerchantRegistryContract, MerchantRegistryContractClient};
 use reputation_contract::{ReputationContract, ReputationContractClient};
 
+#[allow(dead_code)]
 pub struct TestEnv<'a> {
     pub env: Env,
     pub admin: Address,
@@ -29,21 +30,23 @@ impl<'a> TestEnv<'a> {
         let treasury = Address::generate(&env);
         let merchant_fund = Address::generate(&env);
         
-        let token_contract_id = env.register_stellar_asset_contract(token_admin.clone());
+        let token_contract_id = env
+            .register_stellar_asset_contract_v2(token_admin.clone())
+            .address();
         let token = TokenClient::new(&env, &token_contract_id);
         let token_admin_client = StellarAssetClient::new(&env, &token_contract_id);
         
         // Register contracts
-        let creditline_id = env.register_contract(None, CreditLineContract);
+        let creditline_id = env.register(CreditLineContract, ());
         let creditline = CreditLineContractClient::new(&env, &creditline_id);
         
-        let lp_id = env.register_contract(None, LiquidityPoolContract);
+        let lp_id = env.register(LiquidityPoolContract, ());
         let liquidity_pool = LiquidityPoolContractClient::new(&env, &lp_id);
         
-        let registry_id = env.register_contract(None, MerchantRegistryContract);
+        let registry_id = env.register(MerchantRegistryContract, ());
         let merchant_registry = MerchantRegistryContractClient::new(&env, &registry_id);
         
-        let reputation_id = env.register_contract(None, ReputationContract);
+        let reputation_id = env.register(ReputationContract, ());
         let reputation = ReputationContractClient::new(&env, &reputation_id);
         
         // Initialize contracts
@@ -6,7 +6,7 @@ use soroban_sdk::{testutils::Address as _, vec, Address};
 
 #[test]
 fn test_full_bnpl_lifecycle() {
-    let mut setup = TestEnv::setup();
+    let setup = TestEnv::setup();
     let env = &setup.env;
 
     let user = Address::generate(env);
@@ -24,7 +24,7 @@ fn test_full_bnpl_lifecycle() {
     setup.token_admin_client.mint(&lp_provider, &100_000_000_000);
     
     // Deposit into LP
-    setup.liquidity_pool.deposit(&lp_provider, &10_000_000_000);
+    setup.liquidity_pool.deposit(&lp_provider, &10_000_000_000, &0);
 
     // 4. Create loan
     // User needs some token to pay guarantee
@@ -35,14 +35,8 @@ fn test_full_bnpl_lifecycle() {
     
     let installments = vec![
         env,
-        RepaymentInstallment {
-            amount: 300,
-            due_date: 1000,
-        },
-        RepaymentInstallment {
-            amount: 300,
-            due_date: 2000,
-        },
+        RepaymentInstallment::new(1000, 300),
+        RepaymentInstallment::new(2000, 255),
     ];
 
     let loan_id = setup.creditline.create_loan(